@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use solana_winternitz::multibuf;
+
+fn walk(c: &mut Criterion, name: &str, backend: impl Fn(&mut [[u8; 32]], &[u8])) {
+    let chains: [[u8; 32]; 32] = core::array::from_fn(|i| [i as u8; 32]);
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            let mut chains = chains;
+            backend(black_box(&mut chains), &[255; 32]);
+            chains
+        })
+    });
+}
+
+fn bench(c: &mut Criterion) {
+    walk(c, "keccak serial", multibuf::keccak_walk_chains_serial);
+    walk(c, "sha256 serial", multibuf::sha256_walk_chains_serial);
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") {
+        walk(c, "keccak avx2", |chains, steps| unsafe {
+            multibuf::keccak_walk_chains_avx2(chains, steps)
+        });
+        walk(c, "sha256 avx2", |chains, steps| unsafe {
+            multibuf::sha256_walk_chains_avx2(chains, steps)
+        });
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        walk(c, "keccak neon", multibuf::keccak_walk_chains_neon);
+        walk(c, "sha256 neon", multibuf::sha256_walk_chains_neon);
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
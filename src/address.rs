@@ -1,6 +1,9 @@
 use arraystring::{typenum::U44, ArrayString};
 use core::fmt::Write;
 
+use crate::{error::WinternitzError, hash::WinternitzHash};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct WinternitzAddress(pub [u8; 32]);
 
 impl From<[u8; 32]> for WinternitzAddress {
@@ -38,4 +41,27 @@ impl WinternitzAddress {
         write!(&mut array_str, "{}", self).expect("Writing to ArrayString failed");
         array_str
     }
+
+    /// Serializes as `H::ID` followed by the address.
+    pub fn to_tagged_bytes<H: WinternitzHash>(&self) -> [u8; 33] {
+        let mut bytes = [0u8; 33];
+        bytes[0] = H::ID;
+        bytes[1..].copy_from_slice(&self.0);
+        bytes
+    }
+
+    /// Deserializes bytes from `to_tagged_bytes`, failing if they were
+    /// produced with a different hash.
+    pub fn from_tagged_bytes<H: WinternitzHash>(bytes: &[u8]) -> Result<Self, WinternitzError> {
+        let bytes: &[u8; 33] = bytes.try_into().map_err(|_| WinternitzError::InvalidLength)?;
+        if bytes[0] != H::ID {
+            return Err(WinternitzError::HashMismatch {
+                expected: H::ID,
+                found: bytes[0],
+            });
+        }
+        let mut address = [0u8; 32];
+        address.copy_from_slice(&bytes[1..]);
+        Ok(address.into())
+    }
 }
@@ -0,0 +1,110 @@
+//! Damage assessment for reused keys.
+//!
+//! A signature reveals chain `i` at position `256 - v[i]`, from which anyone
+//! can hash forward. Given every signature made with a key, the lowest
+//! revealed position per chain bounds what an attacker can sign: a digest is
+//! forgeable iff each of its bytes is at most `256 - position`.
+
+use crate::{
+    error::WinternitzError,
+    grind::{grind_by, nonce_digest},
+    hash::{walk_chain, WinternitzHash},
+    pubkey::WinternitzPubkey,
+    signature::WinternitzSignature,
+};
+
+pub struct WinternitzReuseAnalysis {
+    pub pubkey: WinternitzPubkey,
+    /// Lowest revealed position per chain, from 1 (secret's first hash) to 256.
+    pub positions: [u16; 32],
+    /// The chain elements at `positions`.
+    pub revealed: [[u8; 32]; 32],
+}
+
+impl WinternitzReuseAnalysis {
+    /// Analyzes `(signature, message)` pairs, failing with `InvalidLength` if
+    /// there are none and `PubkeyMismatch` if they recover different pubkeys.
+    pub fn analyze<H: WinternitzHash>(
+        signatures: &[(&WinternitzSignature, &[u8])],
+    ) -> Result<Self, WinternitzError> {
+        let ((first, message), rest) = signatures
+            .split_first()
+            .ok_or(WinternitzError::InvalidLength)?;
+        let digest = H::hashd(message);
+        let mut analysis = Self {
+            pubkey: first.recover_pubkey_prehashed::<H>(&digest),
+            positions: core::array::from_fn(|i| 256 - digest[i] as u16),
+            revealed: first.0,
+        };
+
+        for (index, (signature, message)) in rest.iter().enumerate() {
+            let digest = H::hashd(message);
+            if signature.recover_pubkey_prehashed::<H>(&digest) != analysis.pubkey {
+                return Err(WinternitzError::PubkeyMismatch { index: index + 1 });
+            }
+            for (i, v) in digest.iter().enumerate() {
+                let position = 256 - *v as u16;
+                if position < analysis.positions[i] {
+                    analysis.positions[i] = position;
+                    analysis.revealed[i] = signature.0[i];
+                }
+            }
+        }
+
+        Ok(analysis)
+    }
+
+    /// Largest digest byte each chain can now be signed for.
+    pub fn max_digits(&self) -> [u8; 32] {
+        core::array::from_fn(|i| (256 - self.positions[i]) as u8)
+    }
+
+    pub fn is_forgeable_digest(&self, digest: &[u8; 32]) -> bool {
+        digest
+            .iter()
+            .zip(self.max_digits())
+            .all(|(v, max)| *v <= max)
+    }
+
+    pub fn is_forgeable<H: WinternitzHash>(&self, message: &[u8]) -> bool {
+        self.is_forgeable_digest(&H::hashd(message))
+    }
+
+    /// `log2` of the fraction of digests that are forgeable, i.e. an attacker
+    /// grinding messages succeeds after about `2^-forgeable_log2()` tries.
+    pub fn forgeable_log2(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(|p| ((257 - *p) as f64 / 256.0).log2())
+            .sum()
+    }
+
+    pub fn forge<H: WinternitzHash>(&self, message: &[u8]) -> Option<WinternitzSignature> {
+        self.forge_prehashed::<H>(&H::hashd(message))
+    }
+
+    pub fn forge_prehashed<H: WinternitzHash>(
+        &self,
+        digest: &[u8; 32],
+    ) -> Option<WinternitzSignature> {
+        if !self.is_forgeable_digest(digest) {
+            return None;
+        }
+        Some(WinternitzSignature(core::array::from_fn(|i| {
+            let steps = 256 - digest[i] as u16 - self.positions[i];
+            walk_chain::<H>(&self.revealed[i], steps as u8)
+        })))
+    }
+
+    /// Searches up to `budget` nonces for a variant of `message` that can be
+    /// forged, as checked by `recover_pubkey_with_nonce`.
+    pub fn forge_with_nonce<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        budget: u64,
+    ) -> Option<(WinternitzSignature, u64)> {
+        let nonce = grind_by::<H, _>(message, budget, |digest| self.is_forgeable_digest(digest))?;
+        let signature = self.forge_prehashed::<H>(&nonce_digest::<H>(message, nonce))?;
+        Some((signature, nonce))
+    }
+}
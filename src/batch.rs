@@ -0,0 +1,91 @@
+//! Batch signing: one key signs the Merkle root of many messages.
+//!
+//! Each message becomes a leaf `H::hashv(&[H::DOMAIN, b"/batch-leaf", message])`
+//! and the key signs `H::hashv(&[H::DOMAIN, b"/batch", count (u32 LE), root])`,
+//! so a batch signature never verifies as a plain signature over the same
+//! bytes or under another hash. A message is then accepted with the shared
+//! signature, its index and authentication path, so every action in the
+//! batch can be executed independently.
+
+#[cfg(not(target_os = "solana"))]
+use std::vec::Vec;
+
+#[cfg(not(target_os = "solana"))]
+use crate::error::WinternitzError;
+use crate::{
+    address::WinternitzAddress, hash::WinternitzHash, merkle, signature::WinternitzSignature,
+};
+
+#[inline(always)]
+pub fn batch_leaf<H: WinternitzHash>(message: &[u8]) -> [u8; 32] {
+    H::hashv(&[H::DOMAIN, b"/batch-leaf", message])
+}
+
+/// The message actually signed for a batch.
+#[inline(always)]
+pub fn batch_message<H: WinternitzHash>(root: &[u8; 32], count: u32) -> [u8; 32] {
+    H::hashv(&[H::DOMAIN, b"/batch", &count.to_le_bytes(), root])
+}
+
+/// Checks that `message` is leaf `index` of a batch of `count` signed by
+/// the key behind `address`.
+pub fn verify_batch<H: WinternitzHash>(
+    signature: &WinternitzSignature,
+    message: &[u8],
+    index: u32,
+    count: u32,
+    path: &[[u8; 32]],
+    address: &WinternitzAddress,
+) -> bool {
+    let leaf = batch_leaf::<H>(message);
+    match merkle::root_from_path::<H>(&leaf, index as usize, count as usize, path) {
+        Some(root) => signature.verify_address::<H>(&batch_message::<H>(&root, count), address),
+        None => false,
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzBatchProof {
+    pub index: u32,
+    pub count: u32,
+    pub path: Vec<[u8; 32]>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl WinternitzBatchProof {
+    pub fn verify<H: WinternitzHash>(
+        &self,
+        signature: &WinternitzSignature,
+        message: &[u8],
+        address: &WinternitzAddress,
+    ) -> bool {
+        verify_batch::<H>(
+            signature, message, self.index, self.count, &self.path, address,
+        )
+    }
+}
+
+/// Root and per-message proofs for a list of messages, building each tree
+/// level once so the whole batch costs `O(n)` hashes.
+#[cfg(not(target_os = "solana"))]
+pub fn batch_tree<H: WinternitzHash>(
+    messages: &[&[u8]],
+) -> Result<([u8; 32], Vec<WinternitzBatchProof>), WinternitzError> {
+    if messages.is_empty() {
+        return Err(WinternitzError::EmptyBatch);
+    }
+    let count = u32::try_from(messages.len()).map_err(|_| WinternitzError::InvalidLength)?;
+
+    let levels = merkle::levels::<H>(messages.iter().map(|m| batch_leaf::<H>(m)).collect());
+    let root = levels[levels.len() - 1][0];
+
+    let proofs = (0..messages.len())
+        .map(|index| WinternitzBatchProof {
+            index: index as u32,
+            count,
+            path: merkle::path_from_levels(&levels, index),
+        })
+        .collect();
+    Ok((root, proofs))
+}
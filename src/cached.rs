@@ -0,0 +1,112 @@
+//! Signer that keeps checkpoints along each chain.
+//!
+//! With an interval of `k`, chain positions `0, k, 2k, ...` are stored, so a
+//! signature costs at most `k - 1` hashes per chain instead of up to 256 and
+//! the pubkey is computed once at construction. Memory is
+//! `32 * 32 * ceil(256 / k)` bytes plus the pubkey; `k = 256` keeps only the
+//! secret, `k = 1` every position.
+
+use core::marker::PhantomData;
+
+use std::vec::Vec;
+
+use crate::{
+    address::WinternitzAddress,
+    hash::{walk_chain, WinternitzHash},
+    privkey::WinternitzPrivkey,
+    pubkey::WinternitzPubkey,
+    signature::WinternitzSignature,
+};
+
+pub struct WinternitzCachedSigner<H: WinternitzHash> {
+    interval: u16,
+    per_chain: usize,
+    checkpoints: Vec<[u8; 32]>,
+    pubkey: WinternitzPubkey,
+    _hash: PhantomData<fn() -> H>,
+}
+
+impl<H: WinternitzHash> WinternitzCachedSigner<H> {
+    /// Panics unless `1 <= interval <= 256`.
+    pub fn new(privkey: &WinternitzPrivkey, interval: u16) -> Self {
+        assert!(
+            (1..=256).contains(&interval),
+            "checkpoint interval must be 1 to 256"
+        );
+        let per_chain = 256usize.div_ceil(interval as usize);
+        let mut checkpoints = std::vec![[0u8; 32]; 32 * per_chain];
+
+        // Walk all 32 chains together, `interval` steps at a time.
+        let mut chains = privkey.0;
+        for checkpoint in 0..per_chain {
+            for (i, chain) in chains.iter().enumerate() {
+                checkpoints[i * per_chain + checkpoint] = *chain;
+            }
+            let mut steps = (256 - checkpoint * interval as usize).min(interval as usize);
+            while steps > 0 {
+                let step = steps.min(255);
+                H::walk_chains(&mut chains, &[step as u8; 32]);
+                steps -= step;
+            }
+        }
+        let pubkey = WinternitzPubkey(chains);
+
+        Self {
+            interval,
+            per_chain,
+            checkpoints,
+            pubkey,
+            _hash: PhantomData,
+        }
+    }
+
+    /// Uses the smallest interval whose checkpoints fit in `bytes`, or
+    /// returns `None` if even an interval of 256 does not.
+    pub fn with_memory(privkey: &WinternitzPrivkey, bytes: usize) -> Option<Self> {
+        (1..=256u16)
+            .find(|k| Self::memory_for(*k) <= bytes)
+            .map(|interval| Self::new(privkey, interval))
+    }
+
+    /// Bytes held by a signer with checkpoint interval `interval`.
+    pub fn memory_for(interval: u16) -> usize {
+        (32 * 256usize.div_ceil(interval as usize) + 32) * 32
+    }
+
+    pub fn interval(&self) -> u16 {
+        self.interval
+    }
+
+    pub fn memory(&self) -> usize {
+        Self::memory_for(self.interval)
+    }
+
+    pub fn pubkey(&self) -> &WinternitzPubkey {
+        &self.pubkey
+    }
+
+    pub fn address(&self) -> WinternitzAddress {
+        self.pubkey.address::<H>()
+    }
+
+    /// Same signature as `WinternitzPrivkey::sign`. Like the private key,
+    /// the signer must only ever sign one message.
+    pub fn sign(&self, message: &[u8]) -> WinternitzSignature {
+        self.sign_prehashed(&H::hashd(message))
+    }
+
+    pub fn sign_prehashed(&self, digest: &[u8; 32]) -> WinternitzSignature {
+        WinternitzSignature(core::array::from_fn(|i| {
+            let position = 256 - digest[i] as u16;
+            if position == 256 {
+                return self.pubkey.0[i];
+            }
+            let checkpoint = position / self.interval;
+            let steps = position - checkpoint * self.interval;
+            walk_chain::<H>(
+                &self.checkpoints[i * self.per_chain + checkpoint as usize],
+                steps as u8,
+            )
+        }))
+    }
+}
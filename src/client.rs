@@ -0,0 +1,211 @@
+//! Instruction encoders for a sample vault program, used by the tests to
+//! drive the split flow end to end.
+//!
+//! No deployed program defines these discriminants or layouts, so they are
+//! not public API; an integration should encode its own program's
+//! instructions from the `split` outputs the same way.
+
+use std::vec::Vec;
+
+use crate::{address::WinternitzAddress, hash::WinternitzHash, privkey::WinternitzPrivkey};
+
+pub const SYSTEM_PROGRAM_ID: [u8; 32] = [0u8; 32];
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinternitzVaultInstruction {
+    Open = 0,
+    Commit = 1,
+    Split = 2,
+    Close = 3,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn new(pubkey: [u8; 32], is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    pub fn new_readonly(pubkey: [u8; 32], is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: [u8; 32],
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// The two instructions of a split-signature flow. `commit` carries the
+/// commitment chains and pairing hash, `execute` the remaining chains and
+/// the action parameters that make up the signed message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzVaultFlow {
+    pub commit: Instruction,
+    pub execute: Instruction,
+}
+
+pub struct WinternitzVaultClient {
+    pub program_id: [u8; 32],
+    pub payer: [u8; 32],
+    pub vault: [u8; 32],
+}
+
+impl WinternitzVaultClient {
+    pub fn new(program_id: [u8; 32], payer: [u8; 32], vault: [u8; 32]) -> Self {
+        Self {
+            program_id,
+            payer,
+            vault,
+        }
+    }
+
+    /// `[0] || address (32) || bump (1)`
+    pub fn open(&self, address: &WinternitzAddress, bump: u8) -> Instruction {
+        let mut data = Vec::with_capacity(34);
+        data.push(WinternitzVaultInstruction::Open as u8);
+        data.extend_from_slice(&address.0);
+        data.push(bump);
+
+        Instruction {
+            program_id: self.program_id,
+            accounts: std::vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    /// Signs `amount || split || refund` and splits the signature into a
+    /// commit and an execute instruction.
+    ///
+    /// Execute data: `[2] || execute chains (4 * 32) || amount (8) || bump (1)`
+    pub fn split<H: WinternitzHash>(
+        &self,
+        privkey: &WinternitzPrivkey,
+        split: [u8; 32],
+        refund: [u8; 32],
+        amount: u64,
+        bump: u8,
+    ) -> WinternitzVaultFlow {
+        let message = split_message(amount, &split, &refund);
+        let (commit, execute_chains) = self.commit::<H>(privkey, &message, bump);
+
+        let mut data = Vec::with_capacity(138);
+        data.push(WinternitzVaultInstruction::Split as u8);
+        data.extend_from_slice(&execute_chains);
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(bump);
+
+        let execute = Instruction {
+            program_id: self.program_id,
+            accounts: std::vec![
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(split, false),
+                AccountMeta::new(refund, false),
+            ],
+            data,
+        };
+
+        WinternitzVaultFlow { commit, execute }
+    }
+
+    /// Signs `refund` and splits the signature into a commit and an execute
+    /// instruction.
+    ///
+    /// Execute data: `[3] || execute chains (4 * 32) || bump (1)`
+    pub fn close<H: WinternitzHash>(
+        &self,
+        privkey: &WinternitzPrivkey,
+        refund: [u8; 32],
+        bump: u8,
+    ) -> WinternitzVaultFlow {
+        let message = close_message(&refund);
+        let (commit, execute_chains) = self.commit::<H>(privkey, &message, bump);
+
+        let mut data = Vec::with_capacity(130);
+        data.push(WinternitzVaultInstruction::Close as u8);
+        data.extend_from_slice(&execute_chains);
+        data.push(bump);
+
+        let execute = Instruction {
+            program_id: self.program_id,
+            accounts: std::vec![
+                AccountMeta::new(self.vault, false),
+                AccountMeta::new(refund, false),
+            ],
+            data,
+        };
+
+        WinternitzVaultFlow { commit, execute }
+    }
+
+    /// Commit data: `[1] || state hash (32) || pairing hash (32) || commitment chains (28 * 32) || bump (1)`
+    ///
+    /// The state hash is `H::hash(message)`, which is what
+    /// `WinternitzCommitmentSignature::recover_address` expects.
+    fn commit<H: WinternitzHash>(
+        &self,
+        privkey: &WinternitzPrivkey,
+        message: &[u8],
+        bump: u8,
+    ) -> (Instruction, [u8; 128]) {
+        let signature = privkey.sign::<H>(message);
+        let (pairing_hash, commitment, execute) = signature.split::<H>(message);
+
+        let mut data = Vec::with_capacity(962);
+        data.push(WinternitzVaultInstruction::Commit as u8);
+        data.extend_from_slice(&H::hash(message));
+        data.extend_from_slice(&pairing_hash);
+        for chain in commitment.0.iter() {
+            data.extend_from_slice(chain);
+        }
+        data.push(bump);
+
+        let mut execute_chains = [0u8; 128];
+        for (dst, chain) in execute_chains.chunks_exact_mut(32).zip(execute.0.iter()) {
+            dst.copy_from_slice(chain);
+        }
+
+        let commit = Instruction {
+            program_id: self.program_id,
+            accounts: std::vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(self.vault, false),
+            ],
+            data,
+        };
+
+        (commit, execute_chains)
+    }
+}
+
+pub fn split_message(amount: u64, split: &[u8; 32], refund: &[u8; 32]) -> [u8; 72] {
+    let mut message = [0u8; 72];
+    message[..8].copy_from_slice(&amount.to_le_bytes());
+    message[8..40].copy_from_slice(split);
+    message[40..].copy_from_slice(refund);
+    message
+}
+
+pub fn close_message(refund: &[u8; 32]) -> [u8; 32] {
+    *refund
+}
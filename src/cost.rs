@@ -0,0 +1,105 @@
+//! Verification cost estimates.
+//!
+//! Recovering a chain from a signature element takes as many hashes as the
+//! corresponding digest byte, so verification cost depends on the message.
+
+use crate::hash::{WinternitzHash, WinternitzKeccak, WinternitzSha256};
+
+/// Syscall pricing for hashes available on-chain. The runtime charges
+/// `BASE_CU` per call plus `max(MIN_SLICE_CU, BYTE_CU * len / 2)` per input
+/// slice.
+pub trait WinternitzSyscallCost: WinternitzHash {
+    const BASE_CU: u64;
+    const BYTE_CU: u64;
+    const MIN_SLICE_CU: u64;
+
+    #[inline(always)]
+    fn hash_cu(slices: &[usize]) -> u64 {
+        slices.iter().fold(Self::BASE_CU, |cu, len| {
+            cu + (Self::BYTE_CU * (*len as u64 / 2)).max(Self::MIN_SLICE_CU)
+        })
+    }
+}
+
+impl WinternitzSyscallCost for WinternitzKeccak {
+    const BASE_CU: u64 = 85;
+    const BYTE_CU: u64 = 1;
+    const MIN_SLICE_CU: u64 = 10;
+}
+
+impl WinternitzSyscallCost for WinternitzSha256 {
+    const BASE_CU: u64 = 85;
+    const BYTE_CU: u64 = 1;
+    const MIN_SLICE_CU: u64 = 10;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WinternitzCost {
+    /// Hashes spent walking chains up to the pubkey.
+    pub chain_hashes: u32,
+    /// `hash_pair` calls building the Merkle root.
+    pub tree_hashes: u32,
+    /// Other single 32-byte hashes, i.e. deriving the digest and the address.
+    pub digest_hashes: u32,
+    /// Hashes over the raw message.
+    pub message_hashes: u32,
+    pub message_len: usize,
+}
+
+impl WinternitzCost {
+    pub fn hashes(&self) -> u32 {
+        self.chain_hashes + self.tree_hashes + self.digest_hashes + self.message_hashes
+    }
+
+    /// Compute units charged for hash syscalls. Program overhead around the
+    /// syscalls is not included and should be budgeted on top.
+    pub fn compute_units<H: WinternitzSyscallCost>(&self) -> u64 {
+        (self.chain_hashes + self.digest_hashes) as u64 * H::hash_cu(&[32])
+            + self.tree_hashes as u64 * H::hash_cu(&[32, 32])
+            + self.message_hashes as u64 * H::hash_cu(&[self.message_len])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WinternitzVerificationCost {
+    /// `WinternitzSignature::recover_address`
+    pub full: WinternitzCost,
+    /// `WinternitzCommitmentSignature::recover_address`, given the state hash
+    pub commitment: WinternitzCost,
+    /// `WinternitzExecuteSignature::recover_pairing_hash`
+    pub execute: WinternitzCost,
+}
+
+pub fn cost<H: WinternitzHash>(message: &[u8]) -> WinternitzVerificationCost {
+    cost_of_digest(&H::hashd(message), message.len())
+}
+
+pub(crate) fn digest_sum(digest: &[u8]) -> u32 {
+    digest.iter().map(|v| *v as u32).sum()
+}
+
+pub(crate) fn cost_of_digest(digest: &[u8; 32], message_len: usize) -> WinternitzVerificationCost {
+    WinternitzVerificationCost {
+        full: WinternitzCost {
+            chain_hashes: digest_sum(digest),
+            tree_hashes: 31,
+            digest_hashes: 2,
+            message_hashes: 1,
+            message_len,
+        },
+        commitment: WinternitzCost {
+            chain_hashes: digest_sum(&digest[..28]),
+            tree_hashes: 28,
+            digest_hashes: 2,
+            message_hashes: 0,
+            message_len: 0,
+        },
+        execute: WinternitzCost {
+            chain_hashes: digest_sum(&digest[28..]),
+            tree_hashes: 3,
+            digest_hashes: 1,
+            message_hashes: 1,
+            message_len,
+        },
+    }
+}
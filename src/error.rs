@@ -0,0 +1,58 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinternitzError {
+    /// Serialized data was produced with a different hash algorithm.
+    HashMismatch { expected: u8, found: u8 },
+    InvalidLength,
+    /// Signature `index` recovers a different pubkey than the first one.
+    PubkeyMismatch {
+        index: usize,
+    },
+    /// A derivation path or path template could not be used.
+    InvalidPath,
+    InvalidPoolFile {
+        line: usize,
+    },
+    /// Pool entry `index` does not match the address derived from the seed.
+    AddressMismatch {
+        index: u32,
+    },
+    InvalidMultisig,
+    /// A secret share is corrupted, inconsistent with the others or does
+    /// not reconstruct the secret it was split from.
+    InvalidShare,
+    NotEnoughShares,
+    InvalidEd25519Signature,
+    InvalidWinternitzSignature,
+    EmptyBatch,
+}
+
+impl core::fmt::Display for WinternitzError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WinternitzError::HashMismatch { expected, found } => write!(
+                f,
+                "hash algorithm mismatch: expected {:#04x}, found {:#04x}",
+                expected, found
+            ),
+            WinternitzError::InvalidLength => f.write_str("invalid length"),
+            WinternitzError::PubkeyMismatch { index } => {
+                write!(f, "signature {} recovers a different pubkey", index)
+            }
+            WinternitzError::InvalidPath => f.write_str("invalid derivation path"),
+            WinternitzError::InvalidPoolFile { line } => {
+                write!(f, "invalid address pool file at line {}", line)
+            }
+            WinternitzError::AddressMismatch { index } => {
+                write!(f, "address at index {} does not match the seed", index)
+            }
+            WinternitzError::InvalidMultisig => f.write_str("invalid multisig"),
+            WinternitzError::InvalidShare => f.write_str("invalid secret share"),
+            WinternitzError::NotEnoughShares => f.write_str("not enough secret shares"),
+            WinternitzError::InvalidEd25519Signature => f.write_str("invalid Ed25519 signature"),
+            WinternitzError::InvalidWinternitzSignature => {
+                f.write_str("invalid Winternitz signature")
+            }
+            WinternitzError::EmptyBatch => f.write_str("batch has no messages"),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Message grinding for cheaper verification.
+//!
+//! A verifier walks each chain as many times as the matching digest byte, so
+//! callers who control a nonce in the message can search for a digest that
+//! is cheap to verify. The nonce is appended to the message as 8
+//! little-endian bytes, so a ground signature is an ordinary signature over
+//! `message || nonce` and verifies through any existing path, including the
+//! commitment and execute split.
+//!
+//! Grinding only applies to the plain, checksum-free encoding. The crate has
+//! no checksummed mode, and target-sum signatures (`target_sum`) always cost
+//! exactly `TARGET_SUM` chain hashes, so there is nothing to grind for.
+
+use crate::{cost::digest_sum, hash::WinternitzHash};
+
+/// `H::hashd(message || nonce.to_le_bytes())`, without concatenating.
+#[inline(always)]
+pub fn nonce_digest<H: WinternitzHash>(message: &[u8], nonce: u64) -> [u8; 32] {
+    H::hash(&H::hashv(&[message, &nonce.to_le_bytes()]))
+}
+
+/// Returns the first nonce below `budget` whose digest costs at most `target`
+/// chain hashes to verify.
+pub fn grind<H: WinternitzHash>(message: &[u8], target: u32, budget: u64) -> Option<u64> {
+    grind_by::<H, _>(message, budget, |digest| digest_sum(digest) <= target)
+}
+
+/// Like `grind`, with a caller-supplied acceptance test on the digest, e.g.
+/// a cap on individual bytes rather than their sum.
+pub fn grind_by<H: WinternitzHash, F: Fn(&[u8; 32]) -> bool>(
+    message: &[u8],
+    budget: u64,
+    accept: F,
+) -> Option<u64> {
+    (0..budget).find(|nonce| accept(&nonce_digest::<H>(message, *nonce)))
+}
@@ -1,13 +1,60 @@
+#[cfg(feature = "digest")]
+use core::marker::PhantomData;
+
+#[cfg(feature = "digest")]
+use digest::{consts::U32, Digest, OutputSizeUser};
+#[cfg(feature = "sha2")]
+use sha2::Sha256;
+#[cfg(feature = "sha3")]
+use sha3::{Keccak256, Sha3_256};
+
+/// A hash function for Winternitz chains, trees and message digests.
+///
+/// `ID` is only checked where it is recorded: tagged serialization rejects
+/// bytes written under another hash with `HashMismatch`, and the `typed`
+/// wrappers fix the hash at compile time. Untagged `recover_*`/`verify*`
+/// take `H` from the caller and cannot tell a wrong hash from a wrong key.
 pub trait WinternitzHash {
+    /// Tag byte identifying the algorithm in serialized keys and signatures.
+    const ID: u8;
+    const NAME: &'static str;
+    /// Domain-separation tag for constructions built on top of this hash.
+    const DOMAIN: &'static [u8];
+
     fn hash(msg: &[u8]) -> [u8; 32];
     fn hashd(msg: &[u8]) -> [u8; 32];
     fn hashv(msg: &[&[u8]]) -> [u8; 32];
     fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32];
+
+    /// Advances each `chains[i]` by `steps[i]` hashes. Off-chain, Keccak and
+    /// SHA-256 override this to hash several chains at once.
+    #[inline(always)]
+    fn walk_chains(chains: &mut [[u8; 32]], steps: &[u8]) {
+        for (chain, steps) in chains.iter_mut().zip(steps) {
+            for _ in 0..*steps {
+                *chain = Self::hash(chain);
+            }
+        }
+    }
+}
+
+/// Advances a chain element by `steps` hashes.
+#[inline(always)]
+pub fn walk_chain<H: WinternitzHash>(element: &[u8; 32], steps: u8) -> [u8; 32] {
+    let mut h = *element;
+    for _ in 0..steps {
+        h = H::hash(&h);
+    }
+    h
 }
 
 pub struct WinternitzKeccak;
 
 impl WinternitzHash for WinternitzKeccak {
+    const ID: u8 = 0;
+    const NAME: &'static str = "keccak";
+    const DOMAIN: &'static [u8] = b"winternitz-keccak";
+
     #[inline(always)]
     fn hash(msg: &[u8]) -> [u8; 32] {
         solana_nostd_keccak::hash(msg)
@@ -27,11 +74,21 @@ impl WinternitzHash for WinternitzKeccak {
     fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
         Self::hashv(&[a, b])
     }
+
+    #[cfg(not(target_os = "solana"))]
+    #[inline(always)]
+    fn walk_chains(chains: &mut [[u8; 32]], steps: &[u8]) {
+        crate::multibuf::keccak_walk_chains(chains, steps)
+    }
 }
 
 pub struct WinternitzSha256;
 
 impl WinternitzHash for WinternitzSha256 {
+    const ID: u8 = 1;
+    const NAME: &'static str = "sha256";
+    const DOMAIN: &'static [u8] = b"winternitz-sha256";
+
     #[inline(always)]
     fn hash(msg: &[u8]) -> [u8; 32] {
         solana_nostd_sha256::hash(msg)
@@ -51,4 +108,373 @@ impl WinternitzHash for WinternitzSha256 {
     fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
         Self::hashv(&[a, b])
     }
+
+    #[cfg(not(target_os = "solana"))]
+    #[inline(always)]
+    fn walk_chains(chains: &mut [[u8; 32]], steps: &[u8]) {
+        crate::multibuf::sha256_walk_chains(chains, steps)
+    }
+}
+
+#[cfg(feature = "sha3")]
+pub struct WinternitzSha3;
+
+#[cfg(feature = "sha3")]
+impl WinternitzHash for WinternitzSha3 {
+    const ID: u8 = 2;
+    const NAME: &'static str = "sha3";
+    const DOMAIN: &'static [u8] = b"winternitz-sha3";
+
+    #[inline(always)]
+    fn hash(msg: &[u8]) -> [u8; 32] {
+        Self::hashv(&[msg])
+    }
+
+    #[inline(always)]
+    fn hashd(msg: &[u8]) -> [u8; 32] {
+        Self::hash(&Self::hash(msg))
+    }
+
+    #[inline(always)]
+    fn hashv(msg: &[&[u8]]) -> [u8; 32] {
+        use sha3::Digest;
+        let mut hasher = Sha3_256::new();
+        for m in msg {
+            hasher.update(m);
+        }
+        hasher.finalize().into()
+    }
+
+    #[inline(always)]
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        Self::hashv(&[a, b])
+    }
+}
+
+#[cfg(feature = "blake3")]
+pub struct WinternitzBlake3;
+
+#[cfg(feature = "blake3")]
+impl WinternitzHash for WinternitzBlake3 {
+    const ID: u8 = 3;
+    const NAME: &'static str = "blake3";
+    const DOMAIN: &'static [u8] = b"winternitz-blake3";
+
+    #[inline(always)]
+    fn hash(msg: &[u8]) -> [u8; 32] {
+        blake3::hash(msg).into()
+    }
+
+    #[inline(always)]
+    fn hashd(msg: &[u8]) -> [u8; 32] {
+        Self::hash(&Self::hash(msg))
+    }
+
+    #[inline(always)]
+    fn hashv(msg: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        for m in msg {
+            hasher.update(m);
+        }
+        hasher.finalize().into()
+    }
+
+    #[inline(always)]
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        Self::hashv(&[a, b])
+    }
+}
+
+/// Poseidon over BN254 (x5, circom parameters), using the `sol_poseidon`
+/// syscall on-chain and `light-poseidon` off-chain.
+///
+/// Poseidon hashes field elements rather than bytes, so input is treated as
+/// one byte string, split into 31-byte big-endian chunks (always below the
+/// field modulus) and terminated by an element holding the byte length.
+/// Elements are absorbed 12 at a time, carrying the previous output as the
+/// first element of each following call.
+#[cfg(feature = "poseidon")]
+pub struct WinternitzPoseidon;
+
+#[cfg(feature = "poseidon")]
+const POSEIDON_MAX_INPUTS: usize = 12;
+#[cfg(feature = "poseidon")]
+const POSEIDON_CHUNK: usize = 31;
+
+/// Incremental state for `WinternitzPoseidon`.
+#[cfg(feature = "poseidon")]
+pub struct WinternitzPoseidonHasher {
+    elements: [[u8; 32]; POSEIDON_MAX_INPUTS],
+    len: usize,
+    chunk: usize,
+    total: u64,
+}
+
+#[cfg(feature = "poseidon")]
+impl WinternitzPoseidonHasher {
+    fn new() -> Self {
+        Self {
+            elements: [[0u8; 32]; POSEIDON_MAX_INPUTS],
+            len: 0,
+            chunk: 0,
+            total: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total += data.len() as u64;
+        while !data.is_empty() {
+            if self.chunk == POSEIDON_CHUNK {
+                self.push_element();
+            }
+            let n = (POSEIDON_CHUNK - self.chunk).min(data.len());
+            self.elements[self.len][1 + self.chunk..1 + self.chunk + n].copy_from_slice(&data[..n]);
+            self.chunk += n;
+            data = &data[n..];
+        }
+    }
+
+    fn push_element(&mut self) {
+        self.len += 1;
+        self.chunk = 0;
+        if self.len == POSEIDON_MAX_INPUTS {
+            self.absorb();
+        }
+    }
+
+    fn absorb(&mut self) -> [u8; 32] {
+        let mut inputs: [&[u8]; POSEIDON_MAX_INPUTS] = [&[]; POSEIDON_MAX_INPUTS];
+        for (input, element) in inputs.iter_mut().zip(self.elements[..self.len].iter()) {
+            *input = element;
+        }
+        let state = solana_poseidon::hashv(
+            solana_poseidon::Parameters::Bn254X5,
+            solana_poseidon::Endianness::BigEndian,
+            &inputs[..self.len],
+        )
+        .expect("Poseidon inputs are always below the field modulus")
+        .to_bytes();
+        self.elements = [[0u8; 32]; POSEIDON_MAX_INPUTS];
+        self.elements[0] = state;
+        self.len = 1;
+        state
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        if self.chunk > 0 {
+            self.push_element();
+        }
+        self.elements[self.len][24..].copy_from_slice(&self.total.to_be_bytes());
+        self.len += 1;
+        self.absorb()
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl WinternitzHash for WinternitzPoseidon {
+    const ID: u8 = 4;
+    const NAME: &'static str = "poseidon";
+    const DOMAIN: &'static [u8] = b"winternitz-poseidon";
+
+    #[inline(always)]
+    fn hash(msg: &[u8]) -> [u8; 32] {
+        Self::hashv(&[msg])
+    }
+
+    #[inline(always)]
+    fn hashd(msg: &[u8]) -> [u8; 32] {
+        Self::hash(&Self::hash(msg))
+    }
+
+    fn hashv(msg: &[&[u8]]) -> [u8; 32] {
+        let mut sponge = WinternitzPoseidonHasher::new();
+        for m in msg {
+            sponge.update(m);
+        }
+        sponge.finalize()
+    }
+
+    #[inline(always)]
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        Self::hashv(&[a, b])
+    }
+}
+
+/// A RustCrypto `Digest` with a 32-byte output and the metadata
+/// `WinternitzDigest` needs. Each adapted digest implements this on its own
+/// marker type with its own ID, name and domain tag.
+///
+/// IDs below `0x80` are reserved for the built-in hashes.
+#[cfg(feature = "digest")]
+pub trait WinternitzDigestAlgorithm {
+    type Digest: Digest + OutputSizeUser<OutputSize = U32>;
+    const ID: u8;
+    const NAME: &'static str;
+    const DOMAIN: &'static [u8];
+}
+
+/// Adapter implementing `WinternitzHash` for a `WinternitzDigestAlgorithm`.
+#[cfg(feature = "digest")]
+pub struct WinternitzDigest<A>(PhantomData<A>);
+
+#[cfg(feature = "digest")]
+impl<A: WinternitzDigestAlgorithm> WinternitzHash for WinternitzDigest<A> {
+    const ID: u8 = {
+        assert!(A::ID >= 0x80, "IDs below 0x80 are reserved for built-in hashes");
+        A::ID
+    };
+    const NAME: &'static str = A::NAME;
+    const DOMAIN: &'static [u8] = A::DOMAIN;
+
+    #[inline(always)]
+    fn hash(msg: &[u8]) -> [u8; 32] {
+        A::Digest::digest(msg).into()
+    }
+
+    #[inline(always)]
+    fn hashd(msg: &[u8]) -> [u8; 32] {
+        Self::hash(&Self::hash(msg))
+    }
+
+    #[inline(always)]
+    fn hashv(msg: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = A::Digest::new();
+        for m in msg {
+            Digest::update(&mut hasher, m);
+        }
+        hasher.finalize().into()
+    }
+
+    #[inline(always)]
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        Self::hashv(&[a, b])
+    }
+}
+
+/// Hashes that can absorb a message incrementally. `finalize` must equal
+/// `hash` over the concatenation of every `update`.
+pub trait WinternitzStreamingHash: WinternitzHash {
+    type Hasher;
+
+    fn hasher() -> Self::Hasher;
+    fn update(hasher: &mut Self::Hasher, data: &[u8]);
+    fn finalize(hasher: Self::Hasher) -> [u8; 32];
+}
+
+#[cfg(any(feature = "sha2", feature = "sha3"))]
+macro_rules! digest_streaming_hash {
+    ($hash:ty, $digest:ty) => {
+        impl WinternitzStreamingHash for $hash {
+            type Hasher = $digest;
+
+            fn hasher() -> Self::Hasher {
+                <$digest>::new()
+            }
+
+            fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+                Digest::update(hasher, data);
+            }
+
+            fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+                hasher.finalize().into()
+            }
+        }
+    };
+}
+
+// Software implementations; on-chain the Keccak and SHA-256 syscalls only
+// hash complete inputs.
+#[cfg(feature = "sha3")]
+digest_streaming_hash!(WinternitzKeccak, Keccak256);
+#[cfg(feature = "sha2")]
+digest_streaming_hash!(WinternitzSha256, Sha256);
+#[cfg(feature = "sha3")]
+digest_streaming_hash!(WinternitzSha3, Sha3_256);
+
+#[cfg(feature = "digest")]
+impl<A: WinternitzDigestAlgorithm> WinternitzStreamingHash for WinternitzDigest<A> {
+    type Hasher = A::Digest;
+
+    fn hasher() -> Self::Hasher {
+        A::Digest::new()
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        Digest::update(hasher, data);
+    }
+
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        hasher.finalize().into()
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl WinternitzStreamingHash for WinternitzBlake3 {
+    type Hasher = blake3::Hasher;
+
+    fn hasher() -> Self::Hasher {
+        blake3::Hasher::new()
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        hasher.update(data);
+    }
+
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        hasher.finalize().into()
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl WinternitzStreamingHash for WinternitzPoseidon {
+    type Hasher = WinternitzPoseidonHasher;
+
+    fn hasher() -> Self::Hasher {
+        WinternitzPoseidonHasher::new()
+    }
+
+    fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+        hasher.update(data);
+    }
+
+    fn finalize(hasher: Self::Hasher) -> [u8; 32] {
+        hasher.finalize()
+    }
+}
+
+/// Computes the signing digest of a message fed in pieces.
+///
+/// `finalize_state_hash` returns `H::hash(message)`, the state hash taken by
+/// `WinternitzCommitmentSignature::recover_address`, and `finalize` returns
+/// `H::hash(H::hash(message))`, i.e. `H::hashd(message)`, which is what
+/// `sign_prehashed` and the `*_prehashed` recovery functions expect.
+pub struct WinternitzMessageHasher<H: WinternitzStreamingHash> {
+    hasher: H::Hasher,
+}
+
+impl<H: WinternitzStreamingHash> Default for WinternitzMessageHasher<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: WinternitzStreamingHash> WinternitzMessageHasher<H> {
+    pub fn new() -> Self {
+        Self {
+            hasher: H::hasher(),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        H::update(&mut self.hasher, data);
+        self
+    }
+
+    pub fn finalize_state_hash(self) -> [u8; 32] {
+        H::finalize(self.hasher)
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        H::hash(&self.finalize_state_hash())
+    }
 }
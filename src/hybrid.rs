@@ -0,0 +1,183 @@
+//! Hybrid Ed25519 + Winternitz signatures for the post-quantum transition.
+//!
+//! A hybrid address binds an Ed25519 pubkey and a `WinternitzAddress`. Both
+//! halves sign the same 32-byte digest,
+//! `H::hashv(&[H::DOMAIN, b"/hybrid", ed25519, winternitz, message])`, so
+//! neither signature can be lifted out and replayed on its own. A hybrid
+//! signature is valid only if both halves verify.
+//!
+//! Serialization is fixed-size: an address is `ed25519 (32) || winternitz
+//! (32)` and a signature `ed25519 (64) || winternitz chains (32 * 32)`.
+//! On-chain, check the Winternitz half with `verify_winternitz` and the
+//! Ed25519 half over `hybrid_message` with the Ed25519 program.
+
+#[cfg(not(target_os = "solana"))]
+use core::marker::PhantomData;
+
+#[cfg(not(target_os = "solana"))]
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+#[cfg(not(target_os = "solana"))]
+use crate::privkey::WinternitzPrivkey;
+use crate::{
+    address::WinternitzAddress, error::WinternitzError, hash::WinternitzHash,
+    signature::WinternitzSignature,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WinternitzHybridAddress {
+    pub ed25519: [u8; 32],
+    pub winternitz: WinternitzAddress,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WinternitzHybridSignature {
+    pub ed25519: [u8; 64],
+    pub winternitz: WinternitzSignature,
+}
+
+/// The digest both halves of a hybrid signature sign.
+#[inline(always)]
+pub fn hybrid_message<H: WinternitzHash>(
+    address: &WinternitzHybridAddress,
+    message: &[u8],
+) -> [u8; 32] {
+    H::hashv(&[
+        H::DOMAIN,
+        b"/hybrid",
+        &address.ed25519,
+        &address.winternitz.0,
+        message,
+    ])
+}
+
+impl WinternitzHybridAddress {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.ed25519);
+        bytes[32..].copy_from_slice(&self.winternitz.0);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WinternitzError> {
+        let bytes: &[u8; 64] = bytes
+            .try_into()
+            .map_err(|_| WinternitzError::InvalidLength)?;
+        let mut ed25519 = [0u8; 32];
+        let mut winternitz = [0u8; 32];
+        ed25519.copy_from_slice(&bytes[..32]);
+        winternitz.copy_from_slice(&bytes[32..]);
+        Ok(Self {
+            ed25519,
+            winternitz: winternitz.into(),
+        })
+    }
+}
+
+impl WinternitzHybridSignature {
+    pub const LEN: usize = 64 + 32 * 32;
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..64].copy_from_slice(&self.ed25519);
+        for (dst, chain) in bytes[64..]
+            .chunks_exact_mut(32)
+            .zip(self.winternitz.0.iter())
+        {
+            dst.copy_from_slice(chain);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WinternitzError> {
+        if bytes.len() != Self::LEN {
+            return Err(WinternitzError::InvalidLength);
+        }
+        let mut ed25519 = [0u8; 64];
+        ed25519.copy_from_slice(&bytes[..64]);
+        let mut winternitz = [[0u8; 32]; 32];
+        for (chain, src) in winternitz.iter_mut().zip(bytes[64..].chunks_exact(32)) {
+            chain.copy_from_slice(src);
+        }
+        Ok(Self {
+            ed25519,
+            winternitz: WinternitzSignature(winternitz),
+        })
+    }
+
+    /// Checks the Winternitz half only, for programs that verify the
+    /// Ed25519 half through the Ed25519 program.
+    pub fn verify_winternitz<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzHybridAddress,
+    ) -> bool {
+        self.winternitz
+            .verify_address::<H>(&hybrid_message::<H>(address, message), &address.winternitz)
+    }
+
+    /// Fails with `InvalidEd25519Signature` or `InvalidWinternitzSignature`
+    /// if either half does not verify.
+    #[cfg(not(target_os = "solana"))]
+    pub fn verify<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzHybridAddress,
+    ) -> Result<(), WinternitzError> {
+        let digest = hybrid_message::<H>(address, message);
+        VerifyingKey::from_bytes(&address.ed25519)
+            .and_then(|key| key.verify_strict(&digest, &Signature::from_bytes(&self.ed25519)))
+            .map_err(|_| WinternitzError::InvalidEd25519Signature)?;
+        if !self.verify_winternitz::<H>(message, address) {
+            return Err(WinternitzError::InvalidWinternitzSignature);
+        }
+        Ok(())
+    }
+}
+
+/// An Ed25519 key and a one-time Winternitz key signing together. The
+/// hybrid address is derived once on construction.
+#[cfg(not(target_os = "solana"))]
+pub struct WinternitzHybridKeypair<H: WinternitzHash> {
+    ed25519: SigningKey,
+    winternitz: WinternitzPrivkey,
+    address: WinternitzHybridAddress,
+    _hash: PhantomData<fn() -> H>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl<H: WinternitzHash> WinternitzHybridKeypair<H> {
+    pub fn generate() -> Self {
+        Self::new(
+            SigningKey::from_bytes(&rand::random()),
+            WinternitzPrivkey::generate(),
+        )
+    }
+
+    pub fn new(ed25519: SigningKey, winternitz: WinternitzPrivkey) -> Self {
+        let address = WinternitzHybridAddress {
+            ed25519: ed25519.verifying_key().to_bytes(),
+            winternitz: winternitz.pubkey::<H>().address::<H>(),
+        };
+        Self {
+            ed25519,
+            winternitz,
+            address,
+            _hash: PhantomData,
+        }
+    }
+
+    pub fn address(&self) -> &WinternitzHybridAddress {
+        &self.address
+    }
+
+    /// Like any Winternitz signature, the Winternitz half makes this keypair
+    /// single-use.
+    pub fn sign(&self, message: &[u8]) -> WinternitzHybridSignature {
+        let digest = hybrid_message::<H>(&self.address, message);
+        WinternitzHybridSignature {
+            ed25519: self.ed25519.sign(&digest).to_bytes(),
+            winternitz: self.winternitz.sign::<H>(&digest),
+        }
+    }
+}
@@ -1,10 +1,43 @@
 #![no_std]
+#[cfg(not(target_os = "solana"))]
+extern crate std;
+
 pub mod address;
+#[cfg(not(target_os = "solana"))]
+pub mod analysis;
+pub mod batch;
+#[cfg(not(target_os = "solana"))]
+pub mod cached;
+#[cfg(test)]
+mod client;
+pub mod cost;
+pub mod error;
+pub mod grind;
 pub mod hash;
+pub mod hybrid;
 pub mod macros;
+pub mod merkle;
+#[cfg(not(target_os = "solana"))]
+pub mod multibuf;
+pub mod multisig;
+#[cfg(not(target_os = "solana"))]
+pub mod onetime;
+#[cfg(all(feature = "parallel", not(target_os = "solana")))]
+pub mod parallel;
 #[cfg(not(target_os = "solana"))]
 pub mod privkey;
+#[cfg(not(target_os = "solana"))]
+pub mod pool;
+pub mod proof;
 pub mod pubkey;
+pub mod salted;
+#[cfg(not(target_os = "solana"))]
+pub mod shamir;
 pub mod signature;
+pub mod target_sum;
 #[cfg(test)]
 pub mod tests;
+pub mod truncated;
+pub mod typed;
+#[cfg(not(target_os = "solana"))]
+pub mod watch;
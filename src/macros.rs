@@ -36,3 +36,41 @@ macro_rules! winternitz_debug {
         }
     };
 }
+
+#[macro_export]
+macro_rules! winternitz_tagged {
+    ($type:ty, $len:expr) => {
+        impl $type {
+            /// Serializes as `H::ID` followed by the chain values.
+            pub fn to_tagged_bytes<H: $crate::hash::WinternitzHash>(&self) -> [u8; 1 + $len * 32] {
+                let mut bytes = [0u8; 1 + $len * 32];
+                bytes[0] = H::ID;
+                for (dst, chain) in bytes[1..].chunks_exact_mut(32).zip(self.0.iter()) {
+                    dst.copy_from_slice(chain);
+                }
+                bytes
+            }
+
+            /// Deserializes bytes from `to_tagged_bytes`, failing if they were
+            /// produced with a different hash.
+            pub fn from_tagged_bytes<H: $crate::hash::WinternitzHash>(
+                bytes: &[u8],
+            ) -> Result<Self, $crate::error::WinternitzError> {
+                if bytes.len() != 1 + $len * 32 {
+                    return Err($crate::error::WinternitzError::InvalidLength);
+                }
+                if bytes[0] != H::ID {
+                    return Err($crate::error::WinternitzError::HashMismatch {
+                        expected: H::ID,
+                        found: bytes[0],
+                    });
+                }
+                let mut chains = [[0u8; 32]; $len];
+                for (chain, src) in chains.iter_mut().zip(bytes[1..].chunks_exact(32)) {
+                    chain.copy_from_slice(src);
+                }
+                Ok(Self(chains))
+            }
+        }
+    };
+}
@@ -0,0 +1,215 @@
+//! Binary Merkle trees over 32-byte leaves.
+//!
+//! A tree of `n > 1` leaves splits at the largest power of two below `n`,
+//! so power-of-two trees are perfectly balanced and e.g. the 32 pubkey
+//! chains hash pairwise exactly as the original hand-unrolled tree did.
+//! Nodes are `H::hash_pair(left, right)`; a single leaf is its own root.
+
+use core::marker::PhantomData;
+
+#[cfg(not(target_os = "solana"))]
+use std::vec::Vec;
+
+use crate::hash::WinternitzHash;
+
+#[inline(always)]
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Root of a non-empty list of leaves.
+pub fn root<H: WinternitzHash>(leaves: &[[u8; 32]]) -> [u8; 32] {
+    root_by(leaves, |left, right| H::hash_pair(left, right))
+}
+
+/// Root with the same shape as `root`, combining nodes with `pair`, e.g.
+/// for truncated nodes.
+pub fn root_by<T: Copy>(leaves: &[T], pair: impl Fn(&T, &T) -> T + Copy) -> T {
+    assert!(!leaves.is_empty(), "Merkle tree needs at least one leaf");
+    if leaves.len() == 1 {
+        return leaves[0];
+    }
+    let k = split(leaves.len());
+    pair(&root_by(&leaves[..k], pair), &root_by(&leaves[k..], pair))
+}
+
+/// Number of siblings in the authentication path of `index`.
+pub fn path_len(leaf_count: usize, mut index: usize) -> usize {
+    let mut n = leaf_count;
+    let mut len = 0;
+    while n > 1 {
+        let k = split(n);
+        if index < k {
+            n = k;
+        } else {
+            index -= k;
+            n -= k;
+        }
+        len += 1;
+    }
+    len
+}
+
+/// Writes the authentication path of `index` into `path`, siblings ordered
+/// from the leaf up, and returns its length.
+pub fn path<H: WinternitzHash>(leaves: &[[u8; 32]], index: usize, path: &mut [[u8; 32]]) -> usize {
+    assert!(index < leaves.len(), "leaf index out of range");
+    if leaves.len() == 1 {
+        return 0;
+    }
+    let k = split(leaves.len());
+    let (depth, sibling) = if index < k {
+        (
+            self::path::<H>(&leaves[..k], index, path),
+            root::<H>(&leaves[k..]),
+        )
+    } else {
+        (
+            self::path::<H>(&leaves[k..], index - k, path),
+            root::<H>(&leaves[..k]),
+        )
+    };
+    path[depth] = sibling;
+    depth + 1
+}
+
+/// Every level of the tree over a non-empty list of leaves, from the leaves
+/// up to the root, so paths for many leaves cost `O(n)` hashes in total.
+/// Pairing neighbours bottom-up and carrying an odd last node up unchanged
+/// gives the same tree as the power-of-two split in `root`.
+#[cfg(not(target_os = "solana"))]
+pub fn levels<H: WinternitzHash>(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    assert!(!leaves.is_empty(), "Merkle tree needs at least one leaf");
+    let mut levels = std::vec![leaves];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => H::hash_pair(left, right),
+                [node] => *node,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Authentication path of `index` read off the output of `levels`, the same
+/// path `path` writes.
+#[cfg(not(target_os = "solana"))]
+pub fn path_from_levels(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut path = Vec::with_capacity(levels.len() - 1);
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            path.push(*sibling);
+        }
+        index >>= 1;
+    }
+    path
+}
+
+/// Recomputes the root from a leaf and its authentication path, returning
+/// `None` if the index or path length do not fit a tree of `leaf_count`.
+pub fn root_from_path<H: WinternitzHash>(
+    leaf: &[u8; 32],
+    mut index: usize,
+    leaf_count: usize,
+    path: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if index >= leaf_count {
+        return None;
+    }
+    // Walk down from the root recording which side the leaf is on.
+    let mut n = leaf_count;
+    let mut rights = 0u64;
+    let mut depth = 0;
+    while n > 1 {
+        let k = split(n);
+        rights <<= 1;
+        if index < k {
+            n = k;
+        } else {
+            rights |= 1;
+            index -= k;
+            n -= k;
+        }
+        depth += 1;
+    }
+    if path.len() != depth {
+        return None;
+    }
+    let mut node = *leaf;
+    for sibling in path {
+        node = if rights & 1 == 1 {
+            H::hash_pair(sibling, &node)
+        } else {
+            H::hash_pair(&node, sibling)
+        };
+        rights >>= 1;
+    }
+    Some(node)
+}
+
+pub fn verify<H: WinternitzHash>(
+    root: &[u8; 32],
+    leaf: &[u8; 32],
+    index: usize,
+    leaf_count: usize,
+    path: &[[u8; 32]],
+) -> bool {
+    root_from_path::<H>(leaf, index, leaf_count, path).as_ref() == Some(root)
+}
+
+/// Incremental root computation holding at most `DEPTH` pending subtree
+/// roots, so leaves can be streamed in without materializing them all.
+/// `DEPTH` must be at least `floor(log2(leaf_count)) + 1`.
+pub struct MerkleStream<H: WinternitzHash, const DEPTH: usize> {
+    nodes: [[u8; 32]; DEPTH],
+    heights: [u8; DEPTH],
+    len: usize,
+    _hash: PhantomData<fn() -> H>,
+}
+
+impl<H: WinternitzHash, const DEPTH: usize> Default for MerkleStream<H, DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: WinternitzHash, const DEPTH: usize> MerkleStream<H, DEPTH> {
+    pub fn new() -> Self {
+        Self {
+            nodes: [[0u8; 32]; DEPTH],
+            heights: [0u8; DEPTH],
+            len: 0,
+            _hash: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn push(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        let mut height = 0;
+        while self.len > 0 && self.heights[self.len - 1] == height {
+            self.len -= 1;
+            node = H::hash_pair(&self.nodes[self.len], &node);
+            height += 1;
+        }
+        self.nodes[self.len] = node;
+        self.heights[self.len] = height;
+        self.len += 1;
+    }
+
+    /// Root of all pushed leaves, identical to `root` over the same list.
+    pub fn finalize(mut self) -> [u8; 32] {
+        assert!(self.len > 0, "Merkle tree needs at least one leaf");
+        self.len -= 1;
+        let mut node = self.nodes[self.len];
+        while self.len > 0 {
+            self.len -= 1;
+            node = H::hash_pair(&self.nodes[self.len], &node);
+        }
+        node
+    }
+}
@@ -0,0 +1,582 @@
+//! Multi-buffer chain hashing for off-chain keygen, signing and recovery.
+//!
+//! Every chain step hashes one 32-byte element, so independent chains can be
+//! packed into SIMD lanes and advanced in lockstep: 8 SHA-256 or 4 Keccak
+//! lanes with AVX2, 4 or 2 with NEON. Chain elements stay transposed in
+//! vector registers for as many steps as every busy lane still needs, so
+//! lanes are only gathered and scattered when a chain finishes. A lane whose
+//! chain is done then picks up the next pending chain, keeping the lanes full
+//! until the queue drains. Without a vector unit the chains are walked one by
+//! one with the scalar hash.
+//!
+//! Both kernels only handle single-block 32-byte inputs, which is all a chain
+//! step ever hashes.
+
+#[cfg(target_arch = "aarch64")]
+use core::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Walks `chains[i]` forward `steps[i]` times with Keccak-256, on the
+/// fastest backend this CPU supports.
+pub fn keccak_walk_chains(chains: &mut [[u8; 32]], steps: &[u8]) {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") {
+        return unsafe { keccak_walk_chains_avx2(chains, steps) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    return keccak_walk_chains_neon(chains, steps);
+    #[allow(unreachable_code)]
+    keccak_walk_chains_serial(chains, steps)
+}
+
+/// Walks `chains[i]` forward `steps[i]` times with SHA-256, on the fastest
+/// backend this CPU supports.
+pub fn sha256_walk_chains(chains: &mut [[u8; 32]], steps: &[u8]) {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") {
+        return unsafe { sha256_walk_chains_avx2(chains, steps) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    return sha256_walk_chains_neon(chains, steps);
+    #[allow(unreachable_code)]
+    sha256_walk_chains_serial(chains, steps)
+}
+
+pub fn keccak_walk_chains_serial(chains: &mut [[u8; 32]], steps: &[u8]) {
+    serial(chains, steps, solana_nostd_keccak::hash)
+}
+
+pub fn sha256_walk_chains_serial(chains: &mut [[u8; 32]], steps: &[u8]) {
+    serial(chains, steps, solana_nostd_sha256::hash)
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn keccak_walk_chains_avx2(chains: &mut [[u8; 32]], steps: &[u8]) {
+    lockstep::<4>(chains, steps, |lanes, rounds| keccak_x4_avx2(lanes, rounds))
+}
+
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+pub unsafe fn sha256_walk_chains_avx2(chains: &mut [[u8; 32]], steps: &[u8]) {
+    lockstep::<8>(chains, steps, |lanes, rounds| sha256_x8_avx2(lanes, rounds))
+}
+
+// NEON is part of the aarch64 baseline, so these need no runtime check.
+#[cfg(target_arch = "aarch64")]
+pub fn keccak_walk_chains_neon(chains: &mut [[u8; 32]], steps: &[u8]) {
+    lockstep::<2>(chains, steps, |lanes, rounds| unsafe {
+        keccak_lanes::<uint64x2_t, 2>(lanes, rounds)
+    })
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn sha256_walk_chains_neon(chains: &mut [[u8; 32]], steps: &[u8]) {
+    lockstep::<4>(chains, steps, |lanes, rounds| unsafe {
+        sha256_lanes::<uint32x4_t, 4>(lanes, rounds)
+    })
+}
+
+fn serial(chains: &mut [[u8; 32]], steps: &[u8], hash: fn(&[u8]) -> [u8; 32]) {
+    for (chain, steps) in chains.iter_mut().zip(steps) {
+        for _ in 0..*steps {
+            *chain = hash(chain);
+        }
+    }
+}
+
+/// Schedules chains onto `N` lanes. Each call to `hash` advances every lane
+/// `rounds` times, as many steps as the busy lane closest to done needs.
+fn lockstep<const N: usize>(
+    chains: &mut [[u8; 32]],
+    steps: &[u8],
+    mut hash: impl FnMut(&mut [[u8; 32]; N], u8),
+) {
+    let mut pending = (0..chains.len().min(steps.len())).filter(|i| steps[*i] > 0);
+    let mut lanes = [[0u8; 32]; N];
+    let mut owner = [None::<usize>; N];
+    let mut remaining = [0u8; N];
+
+    loop {
+        for lane in 0..N {
+            if remaining[lane] > 0 {
+                continue;
+            }
+            if let Some(chain) = owner[lane].take() {
+                chains[chain] = lanes[lane];
+            }
+            if let Some(chain) = pending.next() {
+                owner[lane] = Some(chain);
+                lanes[lane] = chains[chain];
+                remaining[lane] = steps[chain];
+            }
+        }
+        let Some(rounds) = (0..N)
+            .filter(|lane| owner[*lane].is_some())
+            .map(|lane| remaining[lane])
+            .min()
+        else {
+            return;
+        };
+        hash(&mut lanes, rounds);
+        for r in remaining.iter_mut() {
+            *r = r.saturating_sub(rounds);
+        }
+    }
+}
+
+/// `N` 32-bit lanes of a vector register. The methods are `unsafe` because
+/// they compile to vector instructions the CPU must support, e.g. AVX2 for
+/// `__m256i`.
+trait U32Lanes: Copy {
+    unsafe fn splat(x: u32) -> Self;
+    unsafe fn load(x: &[u32]) -> Self;
+    unsafe fn store(self, out: &mut [u32]);
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    /// `!self & other`
+    unsafe fn andnot(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn shl(self, n: u32) -> Self;
+    unsafe fn shr(self, n: u32) -> Self;
+
+    #[inline(always)]
+    unsafe fn rotr(self, n: u32) -> Self {
+        self.shr(n).or(self.shl(32 - n))
+    }
+}
+
+/// `N` 64-bit lanes of a vector register, with the same contract as
+/// `U32Lanes`.
+trait U64Lanes: Copy {
+    unsafe fn splat(x: u64) -> Self;
+    unsafe fn load(x: &[u64]) -> Self;
+    unsafe fn store(self, out: &mut [u64]);
+    unsafe fn xor(self, other: Self) -> Self;
+    /// `!self & other`
+    unsafe fn andnot(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn shl(self, n: u32) -> Self;
+    unsafe fn shr(self, n: u32) -> Self;
+
+    #[inline(always)]
+    unsafe fn rotl(self, n: u32) -> Self {
+        if n == 0 {
+            return self;
+        }
+        self.shl(n).or(self.shr(64 - n))
+    }
+}
+
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Advances each 32-byte lane `rounds` SHA-256 steps, in place. Callers
+/// must ensure the CPU supports `V`'s instructions.
+#[inline(always)]
+unsafe fn sha256_lanes<V: U32Lanes, const N: usize>(lanes: &mut [[u8; 32]; N], rounds: u8) {
+    let mut state: [V; 8] = core::array::from_fn(|j| {
+        let x: [u32; N] = core::array::from_fn(|l| {
+            u32::from_be_bytes(lanes[l][4 * j..4 * j + 4].try_into().unwrap())
+        });
+        V::load(&x)
+    });
+    for _ in 0..rounds {
+        state = sha256_block::<V>(&state);
+    }
+    for (j, word) in state.iter().enumerate() {
+        let mut x = [0u32; N];
+        word.store(&mut x);
+        for (lane, x) in lanes.iter_mut().zip(x) {
+            lane[4 * j..4 * j + 4].copy_from_slice(&x.to_be_bytes());
+        }
+    }
+}
+
+/// SHA-256 of a 32-byte message given as eight big-endian words per lane,
+/// returning the digest in the same form.
+#[inline(always)]
+unsafe fn sha256_block<V: U32Lanes>(message: &[V; 8]) -> [V; 8] {
+    let mut w = [V::splat(0); 64];
+    w[..8].copy_from_slice(message);
+    // Padding of a 256-bit message fills the rest of the block.
+    w[8] = V::splat(0x8000_0000);
+    w[15] = V::splat(256);
+    for t in 16..64 {
+        let s0 = w[t - 15]
+            .rotr(7)
+            .xor(w[t - 15].rotr(18))
+            .xor(w[t - 15].shr(3));
+        let s1 = w[t - 2]
+            .rotr(17)
+            .xor(w[t - 2].rotr(19))
+            .xor(w[t - 2].shr(10));
+        w[t] = s1.add(w[t - 7]).add(s0).add(w[t - 16]);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = SHA256_IV.map(|x| V::splat(x));
+    for t in 0..64 {
+        let s1 = e.rotr(6).xor(e.rotr(11)).xor(e.rotr(25));
+        let ch = e.and(f).xor(e.andnot(g));
+        let t1 = h.add(s1).add(ch).add(V::splat(SHA256_K[t])).add(w[t]);
+        let s0 = a.rotr(2).xor(a.rotr(13)).xor(a.rotr(22));
+        let maj = a.and(b).xor(a.and(c)).xor(b.and(c));
+        let t2 = s0.add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.add(t2);
+    }
+
+    let state = [a, b, c, d, e, f, g, h];
+    core::array::from_fn(|j| state[j].add(V::splat(SHA256_IV[j])))
+}
+
+const KECCAK_RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets indexed by `x + 5 * y`.
+const KECCAK_RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Advances each 32-byte lane `rounds` Keccak-256 (original padding) steps,
+/// in place. Callers must ensure the CPU supports `V`'s instructions.
+#[inline(always)]
+unsafe fn keccak_lanes<V: U64Lanes, const N: usize>(lanes: &mut [[u8; 32]; N], rounds: u8) {
+    let mut a = [V::splat(0); 25];
+    for (j, word) in a.iter_mut().take(4).enumerate() {
+        let x: [u64; N] = core::array::from_fn(|l| {
+            u64::from_le_bytes(lanes[l][8 * j..8 * j + 8].try_into().unwrap())
+        });
+        *word = V::load(&x);
+    }
+    for _ in 0..rounds {
+        // The digest is the next message: clear the rest of the state, then
+        // pad with 0x01 after the message and 0x80 at the end of the rate.
+        for word in a[4..].iter_mut() {
+            *word = V::splat(0);
+        }
+        a[4] = V::splat(0x01);
+        a[16] = V::splat(0x80 << 56);
+        keccak_f::<V>(&mut a);
+    }
+
+    for (j, word) in a.iter().take(4).enumerate() {
+        let mut x = [0u64; N];
+        word.store(&mut x);
+        for (lane, x) in lanes.iter_mut().zip(x) {
+            lane[8 * j..8 * j + 8].copy_from_slice(&x.to_le_bytes());
+        }
+    }
+}
+
+/// Keccak-f[1600] on `N` states at once.
+#[inline(always)]
+unsafe fn keccak_f<V: U64Lanes>(a: &mut [V; 25]) {
+    for rc in KECCAK_RC {
+        let c: [V; 5] = core::array::from_fn(|x| {
+            a[x].xor(a[x + 5])
+                .xor(a[x + 10])
+                .xor(a[x + 15])
+                .xor(a[x + 20])
+        });
+        for x in 0..5 {
+            let d = c[(x + 4) % 5].xor(c[(x + 1) % 5].rotl(1));
+            for y in 0..5 {
+                a[x + 5 * y] = a[x + 5 * y].xor(d);
+            }
+        }
+
+        let mut b = [V::splat(0); 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotl(KECCAK_RHO[x + 5 * y]);
+            }
+        }
+
+        for y in 0..5 {
+            for x in 0..5 {
+                a[x + 5 * y] =
+                    b[x + 5 * y].xor(b[(x + 1) % 5 + 5 * y].andnot(b[(x + 2) % 5 + 5 * y]));
+            }
+        }
+        a[0] = a[0].xor(V::splat(rc));
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sha256_x8_avx2(lanes: &mut [[u8; 32]; 8], rounds: u8) {
+    sha256_lanes::<__m256i, 8>(lanes, rounds)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn keccak_x4_avx2(lanes: &mut [[u8; 32]; 4], rounds: u8) {
+    keccak_lanes::<__m256i, 4>(lanes, rounds)
+}
+
+#[cfg(target_arch = "x86_64")]
+impl U32Lanes for __m256i {
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn splat(x: u32) -> Self {
+        _mm256_set1_epi32(x as i32)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(x: &[u32]) -> Self {
+        assert!(x.len() >= 8);
+        _mm256_loadu_si256(x.as_ptr() as *const __m256i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn store(self, out: &mut [u32]) {
+        assert!(out.len() >= 8);
+        _mm256_storeu_si256(out.as_mut_ptr() as *mut __m256i, self)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn add(self, other: Self) -> Self {
+        _mm256_add_epi32(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn xor(self, other: Self) -> Self {
+        _mm256_xor_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn and(self, other: Self) -> Self {
+        _mm256_and_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn andnot(self, other: Self) -> Self {
+        _mm256_andnot_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn or(self, other: Self) -> Self {
+        _mm256_or_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shl(self, n: u32) -> Self {
+        _mm256_sll_epi32(self, _mm_cvtsi32_si128(n as i32))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shr(self, n: u32) -> Self {
+        _mm256_srl_epi32(self, _mm_cvtsi32_si128(n as i32))
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl U64Lanes for __m256i {
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn splat(x: u64) -> Self {
+        _mm256_set1_epi64x(x as i64)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(x: &[u64]) -> Self {
+        assert!(x.len() >= 4);
+        _mm256_loadu_si256(x.as_ptr() as *const __m256i)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn store(self, out: &mut [u64]) {
+        assert!(out.len() >= 4);
+        _mm256_storeu_si256(out.as_mut_ptr() as *mut __m256i, self)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn xor(self, other: Self) -> Self {
+        _mm256_xor_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn andnot(self, other: Self) -> Self {
+        _mm256_andnot_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn or(self, other: Self) -> Self {
+        _mm256_or_si256(self, other)
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shl(self, n: u32) -> Self {
+        _mm256_sll_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shr(self, n: u32) -> Self {
+        _mm256_srl_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl U32Lanes for uint32x4_t {
+    #[inline(always)]
+    unsafe fn splat(x: u32) -> Self {
+        vdupq_n_u32(x)
+    }
+
+    #[inline(always)]
+    unsafe fn load(x: &[u32]) -> Self {
+        assert!(x.len() >= 4);
+        vld1q_u32(x.as_ptr())
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, out: &mut [u32]) {
+        assert!(out.len() >= 4);
+        vst1q_u32(out.as_mut_ptr(), self)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        vaddq_u32(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        veorq_u32(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        vandq_u32(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn andnot(self, other: Self) -> Self {
+        vbicq_u32(other, self)
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        vorrq_u32(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn shl(self, n: u32) -> Self {
+        vshlq_u32(self, vdupq_n_s32(n as i32))
+    }
+
+    #[inline(always)]
+    unsafe fn shr(self, n: u32) -> Self {
+        vshlq_u32(self, vdupq_n_s32(-(n as i32)))
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl U64Lanes for uint64x2_t {
+    #[inline(always)]
+    unsafe fn splat(x: u64) -> Self {
+        vdupq_n_u64(x)
+    }
+
+    #[inline(always)]
+    unsafe fn load(x: &[u64]) -> Self {
+        assert!(x.len() >= 2);
+        vld1q_u64(x.as_ptr())
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, out: &mut [u64]) {
+        assert!(out.len() >= 2);
+        vst1q_u64(out.as_mut_ptr(), self)
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        veorq_u64(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn andnot(self, other: Self) -> Self {
+        vbicq_u64(other, self)
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        vorrq_u64(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn shl(self, n: u32) -> Self {
+        vshlq_u64(self, vdupq_n_s64(n as i64))
+    }
+
+    #[inline(always)]
+    unsafe fn shr(self, n: u32) -> Self {
+        vshlq_u64(self, vdupq_n_s64(-(n as i64)))
+    }
+}
@@ -0,0 +1,216 @@
+//! M-of-N multisig over Winternitz addresses.
+//!
+//! The multisig address is
+//! `H::hashv(&[H::DOMAIN, b"/multisig", &[m, n], &root])`, where `root` is
+//! the Merkle root of the N member addresses in order. A multisig signature
+//! is at least M member signatures of the same message, each with the
+//! member's index and authentication path, in strictly increasing index
+//! order so no index counts twice. Signatures must also be pairwise
+//! distinct: a key signs a given message deterministically, so this stops a
+//! tree that lists one key at several indices from counting it more than
+//! once.
+//!
+//! Every member key is still one-time: once a message is signed, the
+//! members that signed must not sign anything else.
+
+#[cfg(not(target_os = "solana"))]
+use std::vec::Vec;
+
+#[cfg(not(target_os = "solana"))]
+use crate::error::WinternitzError;
+use crate::{
+    address::WinternitzAddress, hash::WinternitzHash, merkle, signature::WinternitzSignature,
+};
+
+#[inline(always)]
+pub fn multisig_address<H: WinternitzHash>(
+    threshold: u8,
+    member_count: u8,
+    root: &[u8; 32],
+) -> WinternitzAddress {
+    H::hashv(&[H::DOMAIN, b"/multisig", &[threshold, member_count], root]).into()
+}
+
+/// One member's part of a multisig signature, borrowed from instruction data.
+#[derive(Clone, Copy)]
+pub struct WinternitzMultisigMemberRef<'a> {
+    pub index: u8,
+    pub signature: &'a WinternitzSignature,
+    pub path: &'a [[u8; 32]],
+}
+
+/// Checks that at least `threshold` distinct members of the `member_count`
+/// member multisig behind `address` signed `message`.
+pub fn verify_multisig<H: WinternitzHash>(
+    address: &WinternitzAddress,
+    threshold: u8,
+    member_count: u8,
+    message: &[u8],
+    members: &[WinternitzMultisigMemberRef],
+) -> bool {
+    if threshold == 0 || threshold > member_count || members.len() < threshold as usize {
+        return false;
+    }
+    if members
+        .windows(2)
+        .any(|pair| pair[0].index >= pair[1].index)
+    {
+        return false;
+    }
+    if members.iter().enumerate().any(|(i, member)| {
+        members[..i]
+            .iter()
+            .any(|other| other.signature == member.signature)
+    }) {
+        return false;
+    }
+
+    let digest = H::hashd(message);
+    let mut root = None;
+    for member in members {
+        let leaf = member.signature.recover_address_prehashed::<H>(&digest);
+        let Some(member_root) = merkle::root_from_path::<H>(
+            &leaf.0,
+            member.index as usize,
+            member_count as usize,
+            member.path,
+        ) else {
+            return false;
+        };
+        match root {
+            Some(root) if root != member_root => return false,
+            _ => root = Some(member_root),
+        }
+    }
+
+    match root {
+        Some(root) => multisig_address::<H>(threshold, member_count, &root) == *address,
+        None => false,
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzMultisig {
+    threshold: u8,
+    members: Vec<WinternitzAddress>,
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug, PartialEq)]
+pub struct WinternitzMultisigMember {
+    pub index: u8,
+    pub signature: WinternitzSignature,
+    pub path: Vec<[u8; 32]>,
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug, PartialEq)]
+pub struct WinternitzMultisigSignature {
+    pub members: Vec<WinternitzMultisigMember>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl WinternitzMultisig {
+    /// Fails with `InvalidMultisig` unless `1 <= threshold <= members.len()
+    /// <= 255` and the members are distinct.
+    pub fn new(threshold: u8, members: Vec<WinternitzAddress>) -> Result<Self, WinternitzError> {
+        let distinct = members
+            .iter()
+            .enumerate()
+            .all(|(i, member)| !members[..i].contains(member));
+        if threshold == 0 || threshold as usize > members.len() || members.len() > 255 || !distinct
+        {
+            return Err(WinternitzError::InvalidMultisig);
+        }
+        Ok(Self { threshold, members })
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    pub fn members(&self) -> &[WinternitzAddress] {
+        &self.members
+    }
+
+    pub fn member_count(&self) -> u8 {
+        self.members.len() as u8
+    }
+
+    fn leaves(&self) -> Vec<[u8; 32]> {
+        self.members.iter().map(|member| member.0).collect()
+    }
+
+    pub fn root<H: WinternitzHash>(&self) -> [u8; 32] {
+        merkle::root::<H>(&self.leaves())
+    }
+
+    pub fn address<H: WinternitzHash>(&self) -> WinternitzAddress {
+        multisig_address::<H>(self.threshold, self.member_count(), &self.root::<H>())
+    }
+
+    pub fn path<H: WinternitzHash>(&self, index: u8) -> Vec<[u8; 32]> {
+        let leaves = self.leaves();
+        let mut path = std::vec![[0u8; 32]; merkle::path_len(leaves.len(), index as usize)];
+        merkle::path::<H>(&leaves, index as usize, &mut path);
+        path
+    }
+
+    /// Bundles member signatures of `message`, ordering them by member
+    /// index. Fails with `InvalidMultisig` if a signature is not from a
+    /// member, a member signed twice or there are fewer than the threshold.
+    pub fn combine<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        signatures: Vec<WinternitzSignature>,
+    ) -> Result<WinternitzMultisigSignature, WinternitzError> {
+        let digest = H::hashd(message);
+        let levels = merkle::levels::<H>(self.leaves());
+        let mut members = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            let address = signature.recover_address_prehashed::<H>(&digest);
+            let index = self
+                .members
+                .iter()
+                .position(|member| *member == address)
+                .ok_or(WinternitzError::InvalidMultisig)? as u8;
+            members.push(WinternitzMultisigMember {
+                index,
+                signature,
+                path: merkle::path_from_levels(&levels, index as usize),
+            });
+        }
+        members.sort_by_key(|member| member.index);
+        if members.len() < self.threshold as usize
+            || members
+                .windows(2)
+                .any(|pair| pair[0].index == pair[1].index)
+        {
+            return Err(WinternitzError::InvalidMultisig);
+        }
+        Ok(WinternitzMultisigSignature { members })
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+impl WinternitzMultisigSignature {
+    pub fn verify<H: WinternitzHash>(
+        &self,
+        address: &WinternitzAddress,
+        threshold: u8,
+        member_count: u8,
+        message: &[u8],
+    ) -> bool {
+        let members: Vec<WinternitzMultisigMemberRef> = self
+            .members
+            .iter()
+            .map(|member| WinternitzMultisigMemberRef {
+                index: member.index,
+                signature: &member.signature,
+                path: &member.path,
+            })
+            .collect();
+        verify_multisig::<H>(address, threshold, member_count, message, &members)
+    }
+}
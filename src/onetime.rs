@@ -0,0 +1,108 @@
+//! Move-only signing keys that can produce exactly one signature.
+//!
+//! `OneTimeSigningKey::sign` consumes the key, so a second signature with the
+//! same chains is a compile error rather than an operational mistake:
+//!
+//! ```compile_fail
+//! use solana_winternitz::{hash::WinternitzKeccak, onetime::OneTimeSigningKey};
+//!
+//! let key = OneTimeSigningKey::<WinternitzKeccak>::generate();
+//! let (_signature, _used) = key.sign(b"first");
+//! let (_signature, _used) = key.sign(b"second");
+//! ```
+//!
+//! The guarantee is only as strong as the type system's view of the key.
+//! `from_seed` and `from_untyped` will happily rebuild the same chains, so a
+//! caller holding the seed or an untyped copy can still sign twice. The
+//! secret chains are zeroized when the key is dropped.
+
+use core::marker::PhantomData;
+
+use zeroize::Zeroize;
+
+use crate::{
+    hash::WinternitzHash,
+    privkey::WinternitzPrivkey,
+    typed::{TypedAddress, TypedPrivkey, TypedPubkey, TypedSignature},
+};
+
+pub struct OneTimeSigningKey<H: WinternitzHash> {
+    privkey: WinternitzPrivkey,
+    _hash: PhantomData<fn() -> H>,
+}
+
+/// What is left of a `OneTimeSigningKey` after signing: the public half only.
+pub struct UsedKey<H: WinternitzHash> {
+    pubkey: TypedPubkey<H>,
+}
+
+impl<H: WinternitzHash> OneTimeSigningKey<H> {
+    pub fn generate() -> Self {
+        Self::from_untyped(WinternitzPrivkey::generate())
+    }
+
+    pub fn from_seed(seed: [u8; 64], path: &str) -> Result<Self, bip32::Error> {
+        WinternitzPrivkey::from_seed(seed, path).map(Self::from_untyped)
+    }
+
+    pub fn from_untyped(privkey: WinternitzPrivkey) -> Self {
+        Self {
+            privkey,
+            _hash: PhantomData,
+        }
+    }
+
+    pub fn pubkey(&self) -> TypedPubkey<H> {
+        TypedPubkey::from_untyped(self.privkey.pubkey::<H>())
+    }
+
+    pub fn address(&self) -> TypedAddress<H> {
+        self.pubkey().address()
+    }
+
+    /// Signs `message` and zeroizes the secret chains. The pubkey is recovered
+    /// from the signature, which finishes each chain instead of rehashing it.
+    pub fn sign(self, message: &[u8]) -> (TypedSignature<H>, UsedKey<H>) {
+        let signature = TypedSignature::from_untyped(self.privkey.sign::<H>(message));
+        let used = UsedKey {
+            pubkey: signature.recover_pubkey(message),
+        };
+        (signature, used)
+    }
+}
+
+impl<H: WinternitzHash> From<TypedPrivkey<H>> for OneTimeSigningKey<H> {
+    fn from(privkey: TypedPrivkey<H>) -> Self {
+        Self::from_untyped(privkey.into_untyped())
+    }
+}
+
+impl<H: WinternitzHash> Drop for OneTimeSigningKey<H> {
+    fn drop(&mut self) {
+        self.privkey.0.zeroize();
+    }
+}
+
+impl<H: WinternitzHash> UsedKey<H> {
+    pub fn pubkey(&self) -> &TypedPubkey<H> {
+        &self.pubkey
+    }
+
+    pub fn address(&self) -> TypedAddress<H> {
+        self.pubkey.address()
+    }
+}
+
+impl<H: WinternitzHash> core::fmt::Debug for OneTimeSigningKey<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "OneTimeSigningKey<{}>", H::NAME)
+    }
+}
+
+impl<H: WinternitzHash> core::fmt::Debug for UsedKey<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("UsedKey")
+            .field("pubkey", &self.pubkey)
+            .finish()
+    }
+}
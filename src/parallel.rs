@@ -0,0 +1,76 @@
+//! Multi-threaded key derivation and verification, behind the `parallel`
+//! feature. Every function here returns exactly what its serial
+//! counterpart does; only the work is spread across the rayon pool.
+
+use std::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::{
+    address::WinternitzAddress,
+    hash::{walk_chain, WinternitzHash},
+    privkey::WinternitzPrivkey,
+    pubkey::WinternitzPubkey,
+    signature::WinternitzSignature,
+};
+
+impl WinternitzPrivkey {
+    /// `pubkey` with one task per chain.
+    pub fn par_pubkey<H: WinternitzHash>(&self) -> WinternitzPubkey {
+        let mut pubkey: WinternitzPubkey = self.0.into();
+        pubkey.0.par_iter_mut().for_each(|chain| {
+            *chain = walk_chain::<H>(&walk_chain::<H>(chain, 1), 255);
+        });
+        pubkey
+    }
+}
+
+impl WinternitzSignature {
+    /// `recover_pubkey` with one task per chain.
+    pub fn par_recover_pubkey<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzPubkey {
+        let digest = H::hashd(message);
+        let mut pubkey: WinternitzPubkey = self.0.into();
+        pubkey
+            .0
+            .par_iter_mut()
+            .zip(digest.par_iter())
+            .for_each(|(chain, v)| *chain = walk_chain::<H>(chain, *v));
+        pubkey
+    }
+}
+
+/// Addresses of many keys, one task per key.
+pub fn addresses<H: WinternitzHash>(privkeys: &[WinternitzPrivkey]) -> Vec<WinternitzAddress> {
+    privkeys
+        .par_iter()
+        .map(|privkey| privkey.pubkey::<H>().address::<H>())
+        .collect()
+}
+
+/// Derives the keys at `paths` from `seed` and returns their addresses in
+/// order, failing on the first invalid path.
+pub fn addresses_from_seed<H: WinternitzHash, P: AsRef<str> + Sync>(
+    seed: [u8; 64],
+    paths: &[P],
+) -> Result<Vec<WinternitzAddress>, bip32::Error> {
+    // rayon's `Result` collect can stop on any failing task, so collect every
+    // result first to report the error of the earliest path.
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            WinternitzPrivkey::from_seed(seed, path.as_ref())
+                .map(|privkey| privkey.pubkey::<H>().address::<H>())
+        })
+        .collect();
+    results.into_iter().collect()
+}
+
+/// Checks each `(signature, message, address)` triple, one task per triple.
+pub fn verify_many<H: WinternitzHash>(
+    items: &[(&WinternitzSignature, &[u8], &WinternitzAddress)],
+) -> Vec<bool> {
+    items
+        .par_iter()
+        .map(|(signature, message, address)| signature.verify_address::<H>(message, address))
+        .collect()
+}
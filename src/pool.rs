@@ -0,0 +1,203 @@
+//! Address pools pre-derived from a seed and a path template.
+//!
+//! A template is a BIP32 path with an `{index}` placeholder, e.g.
+//! `m/44'/501'/{index}'/0'`. Pools export to a watch-only text file that
+//! holds the hash, template and one `index path address` line per entry,
+//! and no secrets:
+//!
+//! ```text
+//! winternitz-pool v1
+//! hash keccak 0
+//! template m/44'/501'/{index}'/0'
+//! 0 m/44'/501'/0'/0' 8Wk1...
+//! ```
+
+use core::ops::Range;
+
+use std::{format, string::String, vec::Vec};
+
+use crate::{
+    address::WinternitzAddress, error::WinternitzError, hash::WinternitzHash,
+    privkey::WinternitzPrivkey,
+};
+
+pub const POOL_HEADER: &str = "winternitz-pool v1";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzPoolEntry {
+    pub index: u32,
+    pub path: String,
+    pub address: WinternitzAddress,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzAddressPool {
+    /// `WinternitzHash::ID` the addresses were derived with.
+    pub hash: u8,
+    /// `WinternitzHash::NAME` of the same hash, written to watch-only files.
+    pub hash_name: String,
+    pub template: String,
+    pub entries: Vec<WinternitzPoolEntry>,
+}
+
+/// Substitutes `index` into `template`, which must contain `{index}`.
+pub fn pool_path(template: &str, index: u32) -> Result<String, WinternitzError> {
+    if !template.contains("{index}") {
+        return Err(WinternitzError::InvalidPath);
+    }
+    Ok(template.replace("{index}", &format!("{}", index)))
+}
+
+fn derive_address<H: WinternitzHash>(
+    seed: [u8; 64],
+    path: &str,
+) -> Result<WinternitzAddress, WinternitzError> {
+    WinternitzPrivkey::from_seed(seed, path)
+        .map(|privkey| privkey.pubkey::<H>().address::<H>())
+        .map_err(|_| WinternitzError::InvalidPath)
+}
+
+impl WinternitzAddressPool {
+    /// Derives the addresses at `indices` of `template`.
+    pub fn derive<H: WinternitzHash>(
+        seed: [u8; 64],
+        template: &str,
+        indices: Range<u32>,
+    ) -> Result<Self, WinternitzError> {
+        let paths = indices
+            .clone()
+            .map(|index| pool_path(template, index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        #[cfg(feature = "parallel")]
+        let addresses = crate::parallel::addresses_from_seed::<H, _>(seed, &paths)
+            .map_err(|_| WinternitzError::InvalidPath)?;
+        #[cfg(not(feature = "parallel"))]
+        let addresses = paths
+            .iter()
+            .map(|path| derive_address::<H>(seed, path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let entries = indices
+            .zip(paths)
+            .zip(addresses)
+            .map(|((index, path), address)| WinternitzPoolEntry {
+                index,
+                path,
+                address,
+            })
+            .collect();
+
+        Ok(Self {
+            hash: H::ID,
+            hash_name: H::NAME.into(),
+            template: template.into(),
+            entries,
+        })
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &WinternitzAddress> {
+        self.entries.iter().map(|entry| &entry.address)
+    }
+
+    pub fn find(&self, address: &WinternitzAddress) -> Option<&WinternitzPoolEntry> {
+        self.entries.iter().find(|entry| entry.address == *address)
+    }
+
+    /// Re-derives every entry from `seed`, failing on the first path or
+    /// address that does not match.
+    pub fn verify<H: WinternitzHash>(&self, seed: [u8; 64]) -> Result<(), WinternitzError> {
+        if self.hash != H::ID {
+            return Err(WinternitzError::HashMismatch {
+                expected: H::ID,
+                found: self.hash,
+            });
+        }
+        for entry in self.entries.iter() {
+            if pool_path(&self.template, entry.index)? != entry.path
+                || derive_address::<H>(seed, &entry.path)? != entry.address
+            {
+                return Err(WinternitzError::AddressMismatch { index: entry.index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the watch-only file under the stored hash.
+    pub fn to_watch_only(&self) -> String {
+        let mut out = format!(
+            "{}\nhash {} {}\ntemplate {}\n",
+            POOL_HEADER, self.hash_name, self.hash, self.template
+        );
+        for entry in self.entries.iter() {
+            out.push_str(&format!(
+                "{} {} {}\n",
+                entry.index, entry.path, entry.address
+            ));
+        }
+        out
+    }
+
+    /// Parses a file from `to_watch_only`, checking every entry's path
+    /// against the template. Errors carry the 1-based line.
+    pub fn from_watch_only(file: &str) -> Result<Self, WinternitzError> {
+        let mut lines = file
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()));
+        let invalid = |line| WinternitzError::InvalidPoolFile { line };
+
+        match lines.next() {
+            Some((_, POOL_HEADER)) => {}
+            _ => return Err(invalid(1)),
+        }
+        let (hash_name, hash) = match lines.next() {
+            Some((n, line)) => {
+                let mut fields = line
+                    .strip_prefix("hash ")
+                    .ok_or(invalid(n))?
+                    .split_whitespace();
+                let (Some(name), Some(id), None) = (fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(invalid(n));
+                };
+                (name.into(), id.parse().map_err(|_| invalid(n))?)
+            }
+            None => return Err(invalid(2)),
+        };
+        let template: String = match lines.next() {
+            Some((n, line)) => {
+                let template = line.strip_prefix("template ").ok_or(invalid(n))?;
+                pool_path(template, 0).map_err(|_| invalid(n))?;
+                template.into()
+            }
+            None => return Err(invalid(3)),
+        };
+
+        let mut entries = Vec::new();
+        for (n, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            let mut fields = line.split_whitespace();
+            let (Some(index), Some(path), Some(address), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(n));
+            };
+            let index = index.parse().map_err(|_| invalid(n))?;
+            if pool_path(&template, index)? != path {
+                return Err(invalid(n));
+            }
+            entries.push(WinternitzPoolEntry {
+                index,
+                path: path.into(),
+                address: WinternitzAddress::try_from(address).map_err(|_| invalid(n))?,
+            });
+        }
+
+        Ok(Self {
+            hash,
+            hash_name,
+            template,
+            entries,
+        })
+    }
+}
@@ -3,8 +3,17 @@ use core::str::FromStr;
 use bip32::{DerivationPath, Seed, XPrv};
 use rand::random;
 
+use std::vec::Vec;
+
 use crate::{
-    hash::WinternitzHash, pubkey::WinternitzPubkey, signature::WinternitzSignature,
+    batch::{batch_message, batch_tree, WinternitzBatchProof},
+    error::WinternitzError,
+    grind::{grind, nonce_digest},
+    hash::WinternitzHash,
+    pubkey::WinternitzPubkey,
+    salted::{salted_digest, WinternitzSaltedSignature},
+    signature::WinternitzSignature,
+    target_sum::{find_randomizer, WinternitzTargetSumSignature},
     winternitz_debug,
 };
 
@@ -23,7 +32,7 @@ impl WinternitzPrivkey {
     }
 
     pub fn from_seed(seed: [u8;64], path: &str) -> Result<Self, bip32::Error> {
-        let root = XPrv::derive_from_path(&Seed::new(seed), &DerivationPath::from_str(path)?)?;
+        let root = XPrv::derive_from_path(Seed::new(seed), &DerivationPath::from_str(path)?)?;
         Ok(Self([
             root.derive_child(bip32::ChildNumber(0))?.private_key().to_bytes().into(),
             root.derive_child(bip32::ChildNumber(1))?.private_key().to_bytes().into(),
@@ -62,26 +71,78 @@ impl WinternitzPrivkey {
         
     #[inline(always)]
     pub fn sign<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzSignature {
-        let v: [u8; 32] = H::hashd(message);
-        let mut sig: WinternitzSignature = self.0.into();
-        for (i, v) in v.iter().enumerate() {
-            sig.0[i] = H::hash(&sig.0[i]);
-            for _ in 0..!*v {
-                sig.0[i] = H::hash(&sig.0[i]);
-            }
+        self.sign_prehashed::<H>(&H::hashd(message))
+    }
+
+    /// Signs `message || nonce` for the first nonce found by `grind`, so the
+    /// signature costs at most `target` chain hashes to verify. Verify with
+    /// `recover_pubkey_with_nonce` or over the concatenated message.
+    pub fn sign_ground<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        target: u32,
+        budget: u64,
+    ) -> Option<(WinternitzSignature, u64)> {
+        let nonce = grind::<H>(message, target, budget)?;
+        Some((self.sign_prehashed::<H>(&nonce_digest::<H>(message, nonce)), nonce))
+    }
+
+    /// Signs the Merkle root of `messages` once, returning a proof per
+    /// message. Fails with `EmptyBatch` if `messages` is empty.
+    pub fn sign_batch<H: WinternitzHash>(
+        &self,
+        messages: &[&[u8]],
+    ) -> Result<(WinternitzSignature, Vec<WinternitzBatchProof>), WinternitzError> {
+        let (root, proofs) = batch_tree::<H>(messages)?;
+        let signature = self.sign::<H>(&batch_message::<H>(&root, proofs.len() as u32));
+        Ok((signature, proofs))
+    }
+
+    /// Signs with a fresh random salt mixed into the digest.
+    pub fn sign_randomized<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzSaltedSignature {
+        self.sign_salted::<H>(message, &random())
+    }
+
+    pub fn sign_salted<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        salt: &[u8; 32],
+    ) -> WinternitzSaltedSignature {
+        WinternitzSaltedSignature {
+            signature: self.sign_prehashed::<H>(&salted_digest::<H>(message, salt)),
+            salt: *salt,
+        }
+    }
+
+    /// Signs in target-sum mode, grinding a randomizer until the digest
+    /// bytes sum to `TARGET_SUM`.
+    pub fn sign_target_sum<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+    ) -> WinternitzTargetSumSignature {
+        let (randomizer, v) = find_randomizer::<H>(message);
+        WinternitzTargetSumSignature {
+            signature: self.sign_prehashed::<H>(&v),
+            randomizer,
         }
+    }
+
+    /// Signs a digest computed by the caller. It must be derived like
+    /// `H::hashd(message)`, e.g. with `WinternitzMessageHasher`, or the
+    /// signature will not verify through the message-based APIs.
+    #[inline(always)]
+    pub fn sign_prehashed<H: WinternitzHash>(&self, digest: &[u8; 32]) -> WinternitzSignature {
+        // Chain `i` moves `256 - digest[i]` steps, one more than fits in a
+        // `u8`, so the first step is taken up front.
+        let mut sig = WinternitzSignature(self.0.map(|secret| H::hash(&secret)));
+        H::walk_chains(&mut sig.0, &digest.map(|v| !v));
         sig
     }
 
     #[inline(always)]
     pub fn pubkey<H: WinternitzHash>(&self) -> WinternitzPubkey {
-        let mut pubkey: WinternitzPubkey = self.0.into();
-        for i in 0..32 {
-            pubkey.0[i] = H::hash(&pubkey.0[i]);
-            for _ in 0..255 {
-                pubkey.0[i] = H::hash(&pubkey.0[i]);
-            }
-        }
+        let mut pubkey = WinternitzPubkey(self.0.map(|secret| H::hash(&secret)));
+        H::walk_chains(&mut pubkey.0, &[255; 32]);
         pubkey
     }
 }
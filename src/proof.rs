@@ -0,0 +1,141 @@
+#[cfg(not(target_os = "solana"))]
+use std::vec::Vec;
+
+use crate::{address::WinternitzAddress, hash::WinternitzHash, merkle};
+
+/// Proof that a single pubkey chain value belongs to a `WinternitzAddress`,
+/// checkable with 5 `hash_pair`s and one `hash` instead of a full merklize.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct WinternitzChainProof {
+    pub path: [[u8; 32]; 5],
+    pub index: u8,
+}
+
+impl WinternitzChainProof {
+    #[inline(always)]
+    pub fn recover_address<H: WinternitzHash>(
+        &self,
+        chain: &[u8; 32],
+    ) -> Option<WinternitzAddress> {
+        merkle::root_from_path::<H>(chain, self.index as usize, 32, &self.path)
+            .map(|root| H::hash(&root).into())
+    }
+
+    #[inline(always)]
+    pub fn verify<H: WinternitzHash>(&self, chain: &[u8; 32], address: &WinternitzAddress) -> bool {
+        self.recover_address::<H>(chain).as_ref() == Some(address)
+    }
+}
+
+/// Proof that several pubkey chains belong to a `WinternitzAddress`. Chains
+/// whose paths meet share the nodes above the meeting point, so proving `k`
+/// chains needs at most `5 * k` and often far fewer nodes.
+#[cfg(not(target_os = "solana"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzMultiChainProof {
+    /// Bit `i` is set if chain `i` is proven.
+    pub mask: u32,
+    /// Sibling nodes the proven chains cannot produce themselves, level by
+    /// level from the leaves up and left to right within a level.
+    pub nodes: Vec<[u8; 32]>,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl WinternitzMultiChainProof {
+    /// Builds the proof for the chains in `mask` from all 32 pubkey chains.
+    pub fn new<H: WinternitzHash>(chains: &[[u8; 32]; 32], mask: u32) -> Option<Self> {
+        if mask == 0 {
+            return None;
+        }
+        let mut level = *chains;
+        let mut known = mask;
+        let mut nodes = Vec::new();
+        let mut width = 32;
+        while width > 1 {
+            let mut next = 0u32;
+            for i in 0..width / 2 {
+                let (left, right) = ((known >> (2 * i)) & 1, (known >> (2 * i + 1)) & 1);
+                if left | right == 0 {
+                    level[i] = H::hash_pair(&level[2 * i], &level[2 * i + 1]);
+                    continue;
+                }
+                if left == 0 {
+                    nodes.push(level[2 * i]);
+                }
+                if right == 0 {
+                    nodes.push(level[2 * i + 1]);
+                }
+                level[i] = H::hash_pair(&level[2 * i], &level[2 * i + 1]);
+                next |= 1 << i;
+            }
+            known = next;
+            width /= 2;
+        }
+        Some(Self { mask, nodes })
+    }
+
+    /// `chains` are the proven chain values in ascending index order.
+    pub fn recover_address<H: WinternitzHash>(
+        &self,
+        chains: &[[u8; 32]],
+    ) -> Option<WinternitzAddress> {
+        recover_multi_chain_address::<H>(self.mask, chains, &self.nodes)
+    }
+
+    pub fn verify<H: WinternitzHash>(
+        &self,
+        chains: &[[u8; 32]],
+        address: &WinternitzAddress,
+    ) -> bool {
+        self.recover_address::<H>(chains).as_ref() == Some(address)
+    }
+}
+
+/// Recomputes the address from the chains selected by `mask`, in ascending
+/// index order, and the nodes of a `WinternitzMultiChainProof`. Returns
+/// `None` if the number of chains or nodes does not match `mask`.
+pub fn recover_multi_chain_address<H: WinternitzHash>(
+    mask: u32,
+    chains: &[[u8; 32]],
+    nodes: &[[u8; 32]],
+) -> Option<WinternitzAddress> {
+    if mask == 0 || chains.len() != mask.count_ones() as usize {
+        return None;
+    }
+    let mut level = [[0u8; 32]; 32];
+    let mut chains = chains.iter();
+    for (i, node) in level.iter_mut().enumerate() {
+        if (mask >> i) & 1 == 1 {
+            *node = *chains.next()?;
+        }
+    }
+
+    // Pairs are read before their parent overwrites slot `i <= 2 * i`.
+    let mut nodes = nodes.iter();
+    let mut known = mask;
+    let mut width = 32;
+    while width > 1 {
+        let mut next = 0u32;
+        for i in 0..width / 2 {
+            let (left, right) = ((known >> (2 * i)) & 1, (known >> (2 * i + 1)) & 1);
+            if left | right == 0 {
+                continue;
+            }
+            if left == 0 {
+                level[2 * i] = *nodes.next()?;
+            }
+            if right == 0 {
+                level[2 * i + 1] = *nodes.next()?;
+            }
+            level[i] = H::hash_pair(&level[2 * i], &level[2 * i + 1]);
+            next |= 1 << i;
+        }
+        known = next;
+        width /= 2;
+    }
+    if nodes.next().is_some() {
+        return None;
+    }
+    Some(H::hash(&level[0]).into())
+}
@@ -1,4 +1,9 @@
-use crate::{address::WinternitzAddress, hash::WinternitzHash, winternitz_debug};
+#[cfg(not(target_os = "solana"))]
+use crate::proof::WinternitzMultiChainProof;
+use crate::{
+    address::WinternitzAddress, hash::WinternitzHash, merkle, proof::WinternitzChainProof,
+    winternitz_debug, winternitz_tagged,
+};
 
 #[repr(C)]
 #[derive(PartialEq)]
@@ -13,66 +18,43 @@ impl From<[[u8; 32]; 32]> for WinternitzPubkey {
 impl WinternitzPubkey {
     #[inline(always)]
     pub fn merklize<H: WinternitzHash>(&self) -> [u8; 32] {
-        H::hash_pair(
-            &H::hash_pair(
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[0], &self.0[1]),
-                        &H::hash_pair(&self.0[2], &self.0[3]),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[4], &self.0[5]),
-                        &H::hash_pair(&self.0[6], &self.0[7]),
-                    ),
-                ),
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[8], &self.0[9]),
-                        &H::hash_pair(&self.0[10], &self.0[11]),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[12], &self.0[13]),
-                        &H::hash_pair(&self.0[14], &self.0[15]),
-                    ),
-                ),
-            ),
-            &H::hash_pair(
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[16], &self.0[17]),
-                        &H::hash_pair(&self.0[18], &self.0[19]),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[20], &self.0[21]),
-                        &H::hash_pair(&self.0[22], &self.0[23]),
-                    ),
-                ),
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[24], &self.0[25]),
-                        &H::hash_pair(&self.0[26], &self.0[27]),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(&self.0[28], &self.0[29]),
-                        &H::hash_pair(&self.0[30], &self.0[31]),
-                    ),
-                ),
-            ),
-        )
+        merkle::root::<H>(&self.0)
     }
 
     #[inline(always)]
     pub fn pairing_hash<H: WinternitzHash>(&self) -> [u8; 32] {
-        H::hash_pair(
-            &H::hash_pair(&self.0[28], &self.0[29]),
-            &H::hash_pair(&self.0[30], &self.0[31]),
-        )
+        merkle::root::<H>(&self.0[28..])
     }
 
     #[inline(always)]
     pub fn address<H: WinternitzHash>(&self) -> WinternitzAddress {
         H::hash(&self.merklize::<H>()).into()
     }
+
+    /// Returns `None` if `index` is not a chain index (`>= 32`).
+    pub fn chain_proof<H: WinternitzHash>(&self, index: u8) -> Option<WinternitzChainProof> {
+        if index as usize >= self.0.len() {
+            return None;
+        }
+        let mut path = [[0u8; 32]; 5];
+        merkle::path::<H>(&self.0, index as usize, &mut path);
+        Some(WinternitzChainProof { path, index })
+    }
+
+    /// Proof for several chains at once. Returns `None` if `indices` is
+    /// empty or holds an index `>= 32`.
+    #[cfg(not(target_os = "solana"))]
+    pub fn multi_chain_proof<H: WinternitzHash>(
+        &self,
+        indices: &[u8],
+    ) -> Option<WinternitzMultiChainProof> {
+        let mut mask = 0u32;
+        for index in indices {
+            mask |= 1u32.checked_shl(*index as u32)?;
+        }
+        WinternitzMultiChainProof::new::<H>(&self.0, mask)
+    }
 }
 
 winternitz_debug!(WinternitzPubkey, "WinternitzPubkey");
+winternitz_tagged!(WinternitzPubkey, 32);
@@ -0,0 +1,110 @@
+//! Randomized message hashing.
+//!
+//! `sign` hashes the message deterministically, so anyone who can find a
+//! collision in `hashd` before the signer commits can get a signature that
+//! also covers a second message. Mixing a fresh signer-chosen salt into the
+//! digest removes that window: the state hash becomes
+//! `H::hashv(&[H::DOMAIN, b"/salted", salt, message])` and the digest is its
+//! hash, mirroring `H::hashd` for the unsalted commitment path. The salt
+//! travels with the signature.
+
+use crate::{
+    address::WinternitzAddress,
+    hash::WinternitzHash,
+    pubkey::WinternitzPubkey,
+    signature::{WinternitzCommitmentSignature, WinternitzExecuteSignature, WinternitzSignature},
+};
+
+/// State hash to pass to `WinternitzCommitmentSignature::recover_address`.
+#[inline(always)]
+pub fn salted_state_hash<H: WinternitzHash>(message: &[u8], salt: &[u8; 32]) -> [u8; 32] {
+    H::hashv(&[H::DOMAIN, b"/salted", salt, message])
+}
+
+#[inline(always)]
+pub fn salted_digest<H: WinternitzHash>(message: &[u8], salt: &[u8; 32]) -> [u8; 32] {
+    H::hash(&salted_state_hash::<H>(message, salt))
+}
+
+#[repr(C)]
+#[derive(PartialEq)]
+pub struct WinternitzSaltedSignature {
+    pub signature: WinternitzSignature,
+    pub salt: [u8; 32],
+}
+
+impl WinternitzSaltedSignature {
+    #[inline(always)]
+    pub fn recover_pubkey<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzPubkey {
+        self.signature
+            .recover_pubkey_salted::<H>(message, &self.salt)
+    }
+
+    #[inline(always)]
+    pub fn recover_address<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzAddress {
+        self.signature
+            .recover_address_salted::<H>(message, &self.salt)
+    }
+
+    #[inline(always)]
+    pub fn verify_address<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzAddress,
+    ) -> bool {
+        self.recover_address::<H>(message) == *address
+    }
+
+    pub fn split<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+    ) -> (
+        [u8; 32],
+        WinternitzCommitmentSignature,
+        WinternitzExecuteSignature,
+    ) {
+        self.signature
+            .split_prehashed::<H>(&salted_digest::<H>(message, &self.salt))
+    }
+}
+
+impl WinternitzSignature {
+    #[inline(always)]
+    pub fn recover_pubkey_salted<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        salt: &[u8; 32],
+    ) -> WinternitzPubkey {
+        self.recover_pubkey_prehashed::<H>(&salted_digest::<H>(message, salt))
+    }
+
+    #[inline(always)]
+    pub fn recover_address_salted<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        salt: &[u8; 32],
+    ) -> WinternitzAddress {
+        self.recover_address_prehashed::<H>(&salted_digest::<H>(message, salt))
+    }
+}
+
+impl WinternitzCommitmentSignature {
+    pub fn recover_address_salted<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        salt: &[u8; 32],
+        pairing_hash: &[u8; 32],
+    ) -> WinternitzAddress {
+        self.recover_address::<H>(&salted_state_hash::<H>(message, salt), pairing_hash)
+    }
+}
+
+impl WinternitzExecuteSignature {
+    pub fn recover_pairing_hash_salted<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        self.recover_pairing_hash_prehashed::<H>(&salted_digest::<H>(message, salt))
+    }
+}
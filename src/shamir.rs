@@ -0,0 +1,258 @@
+//! Shamir secret sharing over GF(256) for seeds and private keys.
+//!
+//! Each byte of `secret || digest` is shared with its own random polynomial
+//! of degree `threshold - 1` over GF(2^8) (AES polynomial `0x11b`), and
+//! share `i` holds the evaluations at `x = i`. `id` is a random share-set
+//! identifier and `digest` the first four bytes of SHA-256 over
+//! `b"winternitz-shamir" || id || secret`. Like SLIP-39, the digest is shared
+//! along with the secret, so shares reveal nothing about the secret until
+//! `threshold` of them are combined. Encoded shares are
+//!
+//! `version (1) || threshold (1) || index (1) || id (4) || len (2, LE) || data || checksum (4)`
+//!
+//! where `checksum` is the first four bytes of SHA-256 over everything
+//! before it. The checksum catches corrupted shares, the id catches shares
+//! from different splits and the digest catches wrong reconstructions.
+//!
+//! Polynomial coefficients and intermediate copies of the secret are
+//! zeroized before they are freed. The secret returned by `combine` belongs
+//! to the caller, who should zeroize it in turn.
+
+use std::vec::Vec;
+
+use rand::Rng;
+use solana_nostd_sha256::{hash, hashv};
+use zeroize::Zeroize;
+
+use crate::{error::WinternitzError, privkey::WinternitzPrivkey};
+
+pub const SHARE_VERSION: u8 = 2;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct WinternitzShare {
+    pub threshold: u8,
+    /// Evaluation point, from 1 to the number of shares.
+    pub index: u8,
+    /// Random identifier shared by all shares of one split.
+    pub id: [u8; 4],
+    pub data: Vec<u8>,
+}
+
+#[inline(always)]
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(a >> 7));
+        b >>= 1;
+    }
+    product
+}
+
+/// `a^254`, the inverse of a non-zero `a`.
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a4 = gf_mul(a2, a2);
+    let a8 = gf_mul(a4, a4);
+    let a16 = gf_mul(a8, a8);
+    let a32 = gf_mul(a16, a16);
+    let a64 = gf_mul(a32, a32);
+    let a128 = gf_mul(a64, a64);
+    [a2, a4, a8, a16, a32, a64]
+        .iter()
+        .fold(a128, |acc, x| gf_mul(acc, *x))
+}
+
+fn secret_digest(id: &[u8; 4], secret: &[u8]) -> [u8; 4] {
+    let hash = hashv(&[b"winternitz-shamir", id, secret]);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn checksum(bytes: &[u8]) -> [u8; 4] {
+    let hash = hash(bytes);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Splits `secret` into `shares` shares, any `threshold` of which recover it.
+pub fn split(
+    secret: &[u8],
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<WinternitzShare>, WinternitzError> {
+    split_with_rng(secret, threshold, shares, &mut rand::rng())
+}
+
+/// `split` drawing the id and coefficients from `rng`.
+pub fn split_with_rng<R: Rng + ?Sized>(
+    secret: &[u8],
+    threshold: u8,
+    shares: u8,
+    rng: &mut R,
+) -> Result<Vec<WinternitzShare>, WinternitzError> {
+    if threshold == 0
+        || threshold > shares
+        || secret.is_empty()
+        || secret.len() > u16::MAX as usize - 4
+    {
+        return Err(WinternitzError::InvalidShare);
+    }
+    let id: [u8; 4] = rng.random();
+    let digest = secret_digest(&id, secret);
+    let mut out: Vec<WinternitzShare> = (1..=shares)
+        .map(|index| WinternitzShare {
+            threshold,
+            index,
+            id,
+            data: Vec::with_capacity(secret.len() + 4),
+        })
+        .collect();
+
+    let mut coefficients = std::vec![0u8; threshold as usize];
+    for byte in secret.iter().chain(digest.iter()) {
+        coefficients[0] = *byte;
+        rng.fill(&mut coefficients[1..]);
+        for share in out.iter_mut() {
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, c| gf_mul(acc, share.index) ^ c);
+            share.data.push(y);
+        }
+    }
+    coefficients.zeroize();
+
+    Ok(out)
+}
+
+/// Recovers the secret from at least `threshold` shares of it.
+pub fn combine(shares: &[WinternitzShare]) -> Result<Vec<u8>, WinternitzError> {
+    let first = shares.first().ok_or(WinternitzError::NotEnoughShares)?;
+    let shares = shares
+        .get(..first.threshold as usize)
+        .ok_or(WinternitzError::NotEnoughShares)?;
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0
+            || share.data.len() <= 4
+            || share.threshold != first.threshold
+            || share.id != first.id
+            || share.data.len() != first.data.len()
+            || shares[..i].iter().any(|other| other.index == share.index)
+        {
+            return Err(WinternitzError::InvalidShare);
+        }
+    }
+
+    // Lagrange basis polynomials evaluated at zero.
+    let basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+                })
+        })
+        .collect();
+
+    let mut secret: Vec<u8> = (0..first.data.len())
+        .map(|i| {
+            shares
+                .iter()
+                .zip(basis.iter())
+                .fold(0, |acc, (share, l)| acc ^ gf_mul(share.data[i], *l))
+        })
+        .collect();
+
+    let digest = secret.split_off(secret.len() - 4);
+    if secret_digest(&first.id, &secret)[..] != digest[..] {
+        secret.zeroize();
+        return Err(WinternitzError::InvalidShare);
+    }
+    Ok(secret)
+}
+
+pub fn split_seed(
+    seed: &[u8; 64],
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<WinternitzShare>, WinternitzError> {
+    split(seed, threshold, shares)
+}
+
+pub fn combine_seed(shares: &[WinternitzShare]) -> Result<[u8; 64], WinternitzError> {
+    let mut secret = combine(shares)?;
+    let seed = secret
+        .as_slice()
+        .try_into()
+        .map_err(|_| WinternitzError::InvalidLength);
+    secret.zeroize();
+    seed
+}
+
+/// Shares the 1024 secret bytes of `privkey`, chain by chain.
+pub fn split_privkey(
+    privkey: &WinternitzPrivkey,
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<WinternitzShare>, WinternitzError> {
+    split(privkey.0.as_flattened(), threshold, shares)
+}
+
+pub fn combine_privkey(shares: &[WinternitzShare]) -> Result<WinternitzPrivkey, WinternitzError> {
+    let mut secret = combine(shares)?;
+    let privkey = (secret.len() == 1024).then(|| {
+        WinternitzPrivkey(core::array::from_fn(|i| {
+            secret[32 * i..32 * i + 32].try_into().unwrap()
+        }))
+    });
+    secret.zeroize();
+    privkey.ok_or(WinternitzError::InvalidLength)
+}
+
+impl WinternitzShare {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + self.data.len());
+        bytes.push(SHARE_VERSION);
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&(self.data.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&checksum(&bytes));
+        bytes
+    }
+
+    /// Decodes a share from `to_bytes`, failing with `InvalidShare` on a bad
+    /// version or checksum and `InvalidLength` on a truncated share.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WinternitzError> {
+        if bytes.len() < 13 {
+            return Err(WinternitzError::InvalidLength);
+        }
+        let len = u16::from_le_bytes([bytes[7], bytes[8]]) as usize;
+        if bytes.len() != 13 + len {
+            return Err(WinternitzError::InvalidLength);
+        }
+        let (body, sum) = bytes.split_at(9 + len);
+        if bytes[0] != SHARE_VERSION || checksum(body) != sum {
+            return Err(WinternitzError::InvalidShare);
+        }
+        Ok(Self {
+            threshold: bytes[1],
+            index: bytes[2],
+            id: [bytes[3], bytes[4], bytes[5], bytes[6]],
+            data: body[9..].to_vec(),
+        })
+    }
+}
+
+impl core::fmt::Debug for WinternitzShare {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WinternitzShare")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("id", &hex::encode(self.id))
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
@@ -1,4 +1,11 @@
-use crate::{address::WinternitzAddress, hash::WinternitzHash, pubkey::WinternitzPubkey, winternitz_debug};
+use crate::{
+    address::WinternitzAddress,
+    grind::nonce_digest,
+    hash::{walk_chain, WinternitzHash},
+    merkle::{self, MerkleStream},
+    pubkey::WinternitzPubkey,
+    winternitz_debug, winternitz_tagged,
+};
 
 #[repr(C)]
 #[derive(PartialEq)]
@@ -26,51 +33,15 @@ impl WinternitzCommitmentSignature {
             }
         }
 
-        H::hash(
-            &H::hash_pair(
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(
-                            &H::hash_pair(&h[0], &h[1]),
-                            &H::hash_pair(&h[2], &h[3]),
-                        ),
-                        &H::hash_pair(
-                            &H::hash_pair(&h[4], &h[5]),
-                            &H::hash_pair(&h[6], &h[7]),
-                        ),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(
-                            &H::hash_pair(&h[8], &h[9]),
-                            &H::hash_pair(&h[10], &h[11]),
-                        ),
-                        &H::hash_pair(
-                            &H::hash_pair(&h[12], &h[13]),
-                            &H::hash_pair(&h[14], &h[15]),
-                        ),
-                    ),
-                ),
-                &H::hash_pair(
-                    &H::hash_pair(
-                        &H::hash_pair(
-                            &H::hash_pair(&h[16], &h[17]),
-                            &H::hash_pair(&h[18], &h[19]),
-                        ),
-                        &H::hash_pair(
-                            &H::hash_pair(&h[20], &h[21]),
-                            &H::hash_pair(&h[22], &h[23]),
-                        ),
-                    ),
-                    &H::hash_pair(
-                        &H::hash_pair(
-                            &H::hash_pair(&h[24], &h[25]),
-                            &H::hash_pair(&h[26], &h[27]),
-                        ),
-                        pairing_hash
-                    ),
-                ),
-            )
-        ).into()
+        // The last four chains are only known through the pairing hash, which
+        // stands in for their subtree.
+        let mut nodes = [[0u8; 32]; 8];
+        for (node, chains) in nodes.iter_mut().zip(h.chunks_exact(4)) {
+            *node = merkle::root::<H>(chains);
+        }
+        nodes[7] = *pairing_hash;
+
+        H::hash(&merkle::root::<H>(&nodes)).into()
     }
 }
 
@@ -80,41 +51,107 @@ pub struct WinternitzExecuteSignature(pub [[u8; 32]; 4]);
 
 impl WinternitzExecuteSignature {
     pub fn recover_pairing_hash<H: WinternitzHash>(&self, message: &[u8]) -> [u8; 32] {
-        let v = H::hashd(message);
+        self.recover_pairing_hash_prehashed::<H>(&H::hashd(message))
+    }
 
+    /// `digest` is `H::hashd(message)`, e.g. from `WinternitzMessageHasher`.
+    pub fn recover_pairing_hash_prehashed<H: WinternitzHash>(&self, digest: &[u8; 32]) -> [u8; 32] {
         let mut h = self.0;
 
-        for (i, v) in v[28..].iter().enumerate() {
+        for (i, v) in digest[28..].iter().enumerate() {
             for _ in 0..*v {
                 h[i] = H::hash(&h[i]);
             }
         }
 
-        H::hash_pair(
-            &H::hash_pair(&h[0], &h[1]),
-            &H::hash_pair(&h[2], &h[3]),
-        )
+        merkle::root::<H>(&h)
     }
 }
 
 impl WinternitzSignature {
     #[inline(always)]
     pub fn recover_pubkey<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzPubkey {
-        let v = H::hashd(message);
+        self.recover_pubkey_prehashed::<H>(&H::hashd(message))
+    }
+
+    /// Recovers the pubkey of a signature from `WinternitzPrivkey::sign_ground`.
+    #[inline(always)]
+    pub fn recover_pubkey_with_nonce<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        nonce: u64,
+    ) -> WinternitzPubkey {
+        self.recover_pubkey_prehashed::<H>(&nonce_digest::<H>(message, nonce))
+    }
+
+    /// `digest` is `H::hashd(message)`, e.g. from `WinternitzMessageHasher`.
+    #[inline(always)]
+    pub fn recover_pubkey_prehashed<H: WinternitzHash>(
+        &self,
+        digest: &[u8; 32],
+    ) -> WinternitzPubkey {
         let mut pubkey: WinternitzPubkey = self.0.into();
-        for (i, v) in v.iter().enumerate() {
-            for _ in 0..*v {
-                pubkey.0[i] = H::hash(&pubkey.0[i]);
-            }
-        }
+        H::walk_chains(&mut pubkey.0, digest);
         pubkey
     }
 
+    /// Recovers the address without materializing the pubkey, folding each
+    /// recovered chain straight into the Merkle tree.
+    #[inline(always)]
+    pub fn recover_address<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzAddress {
+        self.recover_address_prehashed::<H>(&H::hashd(message))
+    }
+
+    #[inline(always)]
+    pub fn recover_address_with_nonce<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        nonce: u64,
+    ) -> WinternitzAddress {
+        self.recover_address_prehashed::<H>(&nonce_digest::<H>(message, nonce))
+    }
+
+    #[inline(always)]
+    pub fn recover_address_prehashed<H: WinternitzHash>(
+        &self,
+        digest: &[u8; 32],
+    ) -> WinternitzAddress {
+        let mut tree = MerkleStream::<H, 6>::new();
+        for (chain, v) in self.0.iter().zip(digest.iter()) {
+            tree.push(walk_chain::<H>(chain, *v));
+        }
+        H::hash(&tree.finalize()).into()
+    }
+
+    /// Recovers the pubkey value of a single chain, to be checked with a
+    /// `WinternitzChainProof` for the same index. Returns `None` if `index >= 32`.
+    #[inline(always)]
+    pub fn recover_chain<H: WinternitzHash>(&self, message: &[u8], index: u8) -> Option<[u8; 32]> {
+        let element = self.0.get(index as usize)?;
+        Some(walk_chain::<H>(element, H::hashd(message)[index as usize]))
+    }
+
+    #[inline(always)]
+    pub fn verify_address<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzAddress,
+    ) -> bool {
+        self.recover_address::<H>(message) == *address
+    }
+
     pub fn split<H: WinternitzHash>(
         &self,
         message: &[u8],
     ) -> ([u8;32], WinternitzCommitmentSignature, WinternitzExecuteSignature) {
-        let pairing_hash = self.recover_pubkey::<H>(message).pairing_hash::<H>();
+        self.split_prehashed::<H>(&H::hashd(message))
+    }
+
+    pub fn split_prehashed<H: WinternitzHash>(
+        &self,
+        digest: &[u8; 32],
+    ) -> ([u8; 32], WinternitzCommitmentSignature, WinternitzExecuteSignature) {
+        let pairing_hash = self.recover_pubkey_prehashed::<H>(digest).pairing_hash::<H>();
 
         let commitment = WinternitzCommitmentSignature([
             self.0[0],
@@ -156,3 +193,6 @@ impl WinternitzSignature {
 winternitz_debug!(WinternitzSignature, "WinternitzSignature");
 winternitz_debug!(WinternitzCommitmentSignature, "WinternitzCommitmentSignature");
 winternitz_debug!(WinternitzExecuteSignature, "WinternitzExecuteSignature");
+winternitz_tagged!(WinternitzSignature, 32);
+winternitz_tagged!(WinternitzCommitmentSignature, 28);
+winternitz_tagged!(WinternitzExecuteSignature, 4);
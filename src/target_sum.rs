@@ -0,0 +1,124 @@
+//! Target-sum encoding (WOTS+C).
+//!
+//! Instead of checksum chains, the signer grinds a randomizer until the
+//! digest bytes sum to exactly `TARGET_SUM`. Lowering a digest byte only
+//! means hashing its signature element forward, but with the sum fixed,
+//! advancing one chain to forge a different digest would require reversing
+//! another, which needs a preimage. Every signature costs exactly
+//! `TARGET_SUM` chain hashes to verify.
+//!
+//! Digests are domain separated from the plain encoding, but a key should
+//! still only ever be verified under one encoding.
+//!
+//! The encoding is selected by the signature type, as with salted signing,
+//! rather than by a flag on `WinternitzSignature`: the randomizer has to
+//! travel with the signature and every recovery has to reject digests that
+//! miss the target, which the plain `recover_pubkey` never does. The split
+//! flow works the same way, with `target_sum_state_hash` as the state hash.
+
+use crate::{
+    address::WinternitzAddress,
+    cost::digest_sum,
+    hash::WinternitzHash,
+    pubkey::WinternitzPubkey,
+    signature::{WinternitzCommitmentSignature, WinternitzExecuteSignature, WinternitzSignature},
+};
+
+/// Mean digest sum, which keeps the expected number of grinding attempts
+/// near 1,050.
+pub const TARGET_SUM: u32 = 32 * 255 / 2;
+
+/// State hash to pass to `WinternitzCommitmentSignature::recover_address_target_sum`.
+#[inline(always)]
+pub fn target_sum_state_hash<H: WinternitzHash>(message: &[u8], randomizer: &[u8; 8]) -> [u8; 32] {
+    H::hashv(&[H::DOMAIN, b"/target-sum", randomizer, message])
+}
+
+#[inline(always)]
+pub fn target_sum_digest<H: WinternitzHash>(message: &[u8], randomizer: &[u8; 8]) -> [u8; 32] {
+    H::hash(&target_sum_state_hash::<H>(message, randomizer))
+}
+
+#[repr(C)]
+#[derive(PartialEq)]
+pub struct WinternitzTargetSumSignature {
+    pub signature: WinternitzSignature,
+    pub randomizer: [u8; 8],
+}
+
+impl WinternitzTargetSumSignature {
+    /// Returns `None` if the digest does not hit `TARGET_SUM`.
+    #[inline(always)]
+    pub fn recover_pubkey<H: WinternitzHash>(&self, message: &[u8]) -> Option<WinternitzPubkey> {
+        let v = target_sum_digest::<H>(message, &self.randomizer);
+        (digest_sum(&v) == TARGET_SUM).then(|| self.signature.recover_pubkey_prehashed::<H>(&v))
+    }
+
+    #[inline(always)]
+    pub fn recover_address<H: WinternitzHash>(&self, message: &[u8]) -> Option<WinternitzAddress> {
+        let v = target_sum_digest::<H>(message, &self.randomizer);
+        (digest_sum(&v) == TARGET_SUM).then(|| self.signature.recover_address_prehashed::<H>(&v))
+    }
+
+    #[inline(always)]
+    pub fn verify_address<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzAddress,
+    ) -> bool {
+        self.recover_address::<H>(message).as_ref() == Some(address)
+    }
+
+    /// Returns `None` if the digest does not hit `TARGET_SUM`.
+    pub fn split<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+    ) -> Option<(
+        [u8; 32],
+        WinternitzCommitmentSignature,
+        WinternitzExecuteSignature,
+    )> {
+        let v = target_sum_digest::<H>(message, &self.randomizer);
+        (digest_sum(&v) == TARGET_SUM).then(|| self.signature.split_prehashed::<H>(&v))
+    }
+}
+
+impl WinternitzCommitmentSignature {
+    /// Returns `None` if the digest of `state_hash` does not hit `TARGET_SUM`.
+    pub fn recover_address_target_sum<H: WinternitzHash>(
+        &self,
+        state_hash: &[u8; 32],
+        pairing_hash: &[u8; 32],
+    ) -> Option<WinternitzAddress> {
+        (digest_sum(&H::hash(state_hash)) == TARGET_SUM)
+            .then(|| self.recover_address::<H>(state_hash, pairing_hash))
+    }
+}
+
+impl WinternitzExecuteSignature {
+    /// Returns `None` if the digest does not hit `TARGET_SUM`.
+    pub fn recover_pairing_hash_target_sum<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        randomizer: &[u8; 8],
+    ) -> Option<[u8; 32]> {
+        let v = target_sum_digest::<H>(message, randomizer);
+        (digest_sum(&v) == TARGET_SUM).then(|| self.recover_pairing_hash_prehashed::<H>(&v))
+    }
+}
+
+/// Finds the first randomizer whose digest sums to `TARGET_SUM`.
+pub fn find_randomizer<H: WinternitzHash>(message: &[u8]) -> ([u8; 8], [u8; 32]) {
+    (0u64..)
+        .map(|counter| counter.to_le_bytes())
+        .map(|randomizer| (randomizer, target_sum_digest::<H>(message, &randomizer)))
+        .find(|(_, v)| digest_sum(v) == TARGET_SUM)
+        .expect("a target-sum randomizer exists")
+}
+
+impl core::fmt::Debug for WinternitzTargetSumSignature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "randomizer: {}", hex::encode(self.randomizer))?;
+        core::fmt::Debug::fmt(&self.signature, f)
+    }
+}
@@ -1,8 +1,47 @@
 use crate::{
-    address::WinternitzAddress, hash::{WinternitzKeccak, WinternitzHash}, privkey::WinternitzPrivkey,
+    address::WinternitzAddress,
+    analysis::WinternitzReuseAnalysis,
+    batch::{batch_leaf, batch_message, batch_tree},
+    cached::WinternitzCachedSigner,
+    client::{
+        close_message, split_message, AccountMeta, WinternitzVaultClient,
+        WinternitzVaultInstruction, SYSTEM_PROGRAM_ID,
+    },
+    cost::{cost, WinternitzSyscallCost},
+    error::WinternitzError,
+    grind::grind,
+    hash::{
+        WinternitzHash, WinternitzKeccak, WinternitzMessageHasher, WinternitzSha256,
+        WinternitzStreamingHash,
+    },
+    hybrid::{WinternitzHybridAddress, WinternitzHybridKeypair, WinternitzHybridSignature},
+    merkle,
+    multibuf,
+    multisig::{
+        multisig_address, verify_multisig, WinternitzMultisig, WinternitzMultisigMemberRef,
+    },
+    onetime::OneTimeSigningKey,
+    pool::WinternitzAddressPool,
+    privkey::WinternitzPrivkey,
     pubkey::WinternitzPubkey,
+    salted::salted_state_hash,
+    shamir::{self, WinternitzShare},
+    signature::{WinternitzCommitmentSignature, WinternitzExecuteSignature, WinternitzSignature},
+    target_sum::{target_sum_digest, target_sum_state_hash, TARGET_SUM},
+    truncated::{WinternitzPrivkey16, WinternitzTruncatedPrivkey},
+    typed::{TypedPrivkey, TypedSignature},
+    watch::{WinternitzAddressStatus, WinternitzObservation, WinternitzWatchWallet},
 };
 
+#[cfg(feature = "blake3")]
+use crate::hash::WinternitzBlake3;
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+use crate::hash::{WinternitzDigest, WinternitzDigestAlgorithm};
+#[cfg(feature = "poseidon")]
+use crate::hash::WinternitzPoseidon;
+#[cfg(feature = "sha3")]
+use crate::hash::WinternitzSha3;
+
 pub const PRIVKEY: WinternitzPrivkey = WinternitzPrivkey([
     [
         0x04, 0x3a, 0x2d, 0x46, 0x24, 0x77, 0x4c, 0x8c, 0x90, 0x40, 0x49, 0x02, 0x7b, 0x3b, 0xea,
@@ -356,7 +395,7 @@ fn test_keygen() {
 fn test_split_signature() {
     let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
     let (pairing_hash, commitment, execute) = signature.split::<WinternitzKeccak>(MESSAGE);
-    let address_recovered = commitment.recover_address::<WinternitzKeccak>(&WinternitzKeccak::hash(&MESSAGE), &pairing_hash);
+    let address_recovered = commitment.recover_address::<WinternitzKeccak>(&WinternitzKeccak::hash(MESSAGE), &pairing_hash);
     assert_eq!(ADDRESS, address_recovered.to_array_string().as_str());
     let pairing_hash_recovered = execute.recover_pairing_hash::<WinternitzKeccak>(MESSAGE);
     assert_eq!(pairing_hash, pairing_hash_recovered);
@@ -379,3 +418,1189 @@ fn test_bip32_derivation() {
     assert_eq!(privkey.0[0], [0x6f, 0xce, 0x6e, 0xda, 0x05, 0xe3, 0x05, 0xf9, 0x0e, 0x35, 0xfe, 0x20, 0x69, 0x77, 0xd3, 0xfd, 0x00, 0x20, 0xe7, 0xd8, 0x57, 0xd8, 0x67, 0x0b, 0xdd, 0x9a, 0xbf, 0x06, 0xa3, 0x47, 0x03, 0x0e]);
     assert_eq!(privkey.0[31], [0xc3, 0x28, 0x84, 0x40, 0xc3, 0x97, 0x5e, 0x9b, 0xcc, 0x1f, 0x25, 0x3b, 0x65, 0x7e, 0x5d, 0x27, 0x5c, 0xfa, 0xdb, 0xf0, 0x34, 0x6e, 0x4e, 0xe9, 0x9e, 0xe7, 0xc1, 0x83, 0x0f, 0x4f, 0xef, 0x51]);
 }
+
+#[test]
+fn test_client_split_flow() {
+    let client = WinternitzVaultClient::new([1u8; 32], [2u8; 32], [3u8; 32]);
+    let (split, refund, amount) = ([4u8; 32], [5u8; 32], 1_000_000u64);
+    let flow = client.split::<WinternitzKeccak>(&PRIVKEY, split, refund, amount, 255);
+
+    let commit = &flow.commit.data;
+    assert_eq!(commit.len(), 962);
+    assert_eq!(commit[0], WinternitzVaultInstruction::Commit as u8);
+    let state_hash: [u8; 32] = commit[1..33].try_into().unwrap();
+    let pairing_hash: [u8; 32] = commit[33..65].try_into().unwrap();
+    let mut chains = [[0u8; 32]; 28];
+    for (chain, bytes) in chains.iter_mut().zip(commit[65..961].chunks_exact(32)) {
+        chain.copy_from_slice(bytes);
+    }
+    let address = WinternitzCommitmentSignature(chains)
+        .recover_address::<WinternitzKeccak>(&state_hash, &pairing_hash);
+    assert_eq!(ADDRESS, address.to_array_string().as_str());
+
+    let execute = &flow.execute.data;
+    assert_eq!(execute.len(), 138);
+    assert_eq!(execute[0], WinternitzVaultInstruction::Split as u8);
+    let mut chains = [[0u8; 32]; 4];
+    for (chain, bytes) in chains.iter_mut().zip(execute[1..129].chunks_exact(32)) {
+        chain.copy_from_slice(bytes);
+    }
+    let message = split_message(amount, &split, &refund);
+    assert_eq!(WinternitzKeccak::hash(&message), state_hash);
+    assert_eq!(
+        WinternitzExecuteSignature(chains).recover_pairing_hash::<WinternitzKeccak>(&message),
+        pairing_hash
+    );
+    assert_eq!(flow.execute.accounts[1].pubkey, split);
+}
+
+#[test]
+fn test_client_layouts() {
+    let client = WinternitzVaultClient::new([1u8; 32], [2u8; 32], [3u8; 32]);
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+
+    // Open: [0] || address (32) || bump (1)
+    let open = client.open(&address, 254);
+    assert_eq!(open.program_id, [1u8; 32]);
+    assert_eq!(open.data.len(), 34);
+    assert_eq!(open.data[0], 0);
+    assert_eq!(open.data[1..33], ADDRESS_BYTES);
+    assert_eq!(open.data[33], 254);
+    assert_eq!(
+        open.accounts,
+        [
+            AccountMeta::new([2u8; 32], true),
+            AccountMeta::new([3u8; 32], false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ]
+    );
+
+    // Commit: [1] || state hash (32) || pairing hash (32) || 28 chains || bump (1)
+    let refund = [5u8; 32];
+    let flow = client.close::<WinternitzKeccak>(&PRIVKEY, refund, 253);
+    let message = close_message(&refund);
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(&message);
+    let (pairing_hash, commitment, execute) = signature.split::<WinternitzKeccak>(&message);
+    let commit = &flow.commit.data;
+    assert_eq!(commit.len(), 962);
+    assert_eq!(commit[0], 1);
+    assert_eq!(commit[1..33], WinternitzKeccak::hash(&message));
+    assert_eq!(commit[33..65], pairing_hash);
+    for (i, chain) in commitment.0.iter().enumerate() {
+        assert_eq!(commit[65 + 32 * i..97 + 32 * i], *chain);
+    }
+    assert_eq!(commit[961], 253);
+    assert_eq!(
+        flow.commit.accounts,
+        [AccountMeta::new([2u8; 32], true), AccountMeta::new([3u8; 32], false)]
+    );
+
+    // Close: [3] || execute chains (4 * 32) || bump (1)
+    let close = &flow.execute.data;
+    assert_eq!(close.len(), 130);
+    assert_eq!(close[0], 3);
+    for (i, chain) in execute.0.iter().enumerate() {
+        assert_eq!(close[1 + 32 * i..33 + 32 * i], *chain);
+    }
+    assert_eq!(close[129], 253);
+    assert_eq!(
+        flow.execute.accounts,
+        [AccountMeta::new([3u8; 32], false), AccountMeta::new(refund, false)]
+    );
+}
+
+fn roundtrip<H: WinternitzHash>() -> WinternitzAddress {
+    let pubkey = PRIVKEY.pubkey::<H>();
+    let signature = PRIVKEY.sign::<H>(MESSAGE);
+    assert!(signature.recover_pubkey::<H>(MESSAGE) == pubkey);
+    assert!(signature.recover_pubkey::<H>(b"tesu") != pubkey);
+
+    let (pairing_hash, commitment, execute) = signature.split::<H>(MESSAGE);
+    let address = commitment.recover_address::<H>(&H::hash(MESSAGE), &pairing_hash);
+    assert_eq!(address.0, pubkey.address::<H>().0);
+    assert_eq!(execute.recover_pairing_hash::<H>(MESSAGE), pairing_hash);
+
+    assert_eq!(H::hashv(&[b"te", b"st"]), H::hash(MESSAGE));
+    assert_eq!(H::hash_pair(b"te", b"st"), H::hash(MESSAGE));
+    assert_eq!(H::hashd(MESSAGE), H::hash(&H::hash(MESSAGE)));
+    address
+}
+
+#[test]
+fn test_hash_implementations() {
+    #[allow(unused_mut)]
+    let mut addresses = std::vec![
+        roundtrip::<WinternitzKeccak>().0,
+        roundtrip::<WinternitzSha256>().0,
+    ];
+    #[cfg(feature = "sha3")]
+    addresses.push(roundtrip::<WinternitzSha3>().0);
+    #[cfg(feature = "blake3")]
+    addresses.push(roundtrip::<WinternitzBlake3>().0);
+    #[cfg(feature = "poseidon")]
+    addresses.push(roundtrip::<WinternitzPoseidon>().0);
+    assert_eq!(addresses[0], ADDRESS_BYTES);
+    for (i, a) in addresses.iter().enumerate() {
+        for b in addresses[i + 1..].iter() {
+            assert_ne!(a, b);
+        }
+    }
+
+    // Known answers for `hash` and for `hash_pair([1; 32], [2; 32])`.
+    assert_eq!(
+        hex::encode(WinternitzKeccak::hash(MESSAGE)),
+        "9c22ff5f21f0b81b113e63f7db6da94fedef11b2119b4088b89664fb9a3cb658"
+    );
+    assert_eq!(
+        hex::encode(WinternitzKeccak::hash_pair(&[1u8; 32], &[2u8; 32])),
+        "346d8c96a2454213fcc0daff3c96ad0398148181b9fa6488f7ae2c0af5b20aa0"
+    );
+    assert_eq!(
+        hex::encode(WinternitzSha256::hash(MESSAGE)),
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+    assert_eq!(
+        hex::encode(WinternitzSha256::hash_pair(&[1u8; 32], &[2u8; 32])),
+        "f818afd37a6dc3bc92fb44731011277006db4efa6e9023cd7468c02335d22a4d"
+    );
+    #[cfg(feature = "sha3")]
+    assert_eq!(
+        hex::encode(WinternitzSha3::hash(MESSAGE)),
+        "36f028580bb02cc8272a9a020f4200e346e276ae664e45ee80745574e2f5ab80"
+    );
+    #[cfg(feature = "blake3")]
+    assert_eq!(
+        hex::encode(WinternitzBlake3::hash(MESSAGE)),
+        "4878ca0425c739fa427f7eda20fe845f6b2e46ba5fe2a14df5b1e32f50603215"
+    );
+}
+
+#[cfg(feature = "poseidon")]
+#[test]
+fn test_poseidon_encoding() {
+    // The circomlib vector `poseidon([1, 2])` pins the parameters.
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let mut two = [0u8; 32];
+    two[31] = 2;
+    let expected = solana_poseidon::hashv(
+        solana_poseidon::Parameters::Bn254X5,
+        solana_poseidon::Endianness::BigEndian,
+        &[&one, &two],
+    )
+    .unwrap()
+    .to_bytes();
+    assert_eq!(
+        hex::encode(expected),
+        "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+    );
+
+    assert_eq!(
+        hex::encode(WinternitzPoseidon::hash(MESSAGE)),
+        "1f535b16de127a1b6d711e7137cde72150bf2f50149d2db0c6c247705ea087ec"
+    );
+    assert_eq!(
+        hex::encode(WinternitzPoseidon::hash_pair(&[1u8; 32], &[2u8; 32])),
+        "0333c491a76f0f054c08f683e883c0fd72aba85145519bf2303fc8e13c54340d"
+    );
+
+    // 64 bytes pack into 31 + 31 + 2 byte chunks, each behind a zero byte,
+    // then the length.
+    let mut chunks = [[0u8; 32]; 4];
+    chunks[0][1..].copy_from_slice(&[1u8; 31]);
+    chunks[1][1] = 1;
+    chunks[1][2..].copy_from_slice(&[2u8; 30]);
+    chunks[2][1..3].copy_from_slice(&[2u8; 2]);
+    chunks[3][31] = 64;
+    let expected = solana_poseidon::hashv(
+        solana_poseidon::Parameters::Bn254X5,
+        solana_poseidon::Endianness::BigEndian,
+        &[&chunks[0], &chunks[1], &chunks[2], &chunks[3]],
+    )
+    .unwrap()
+    .to_bytes();
+    assert_eq!(WinternitzPoseidon::hash_pair(&[1u8; 32], &[2u8; 32]), expected);
+
+    let mut chunk = [0u8; 32];
+    chunk[1..5].copy_from_slice(MESSAGE);
+    let mut len = [0u8; 32];
+    len[31] = 4;
+    let expected = solana_poseidon::hashv(
+        solana_poseidon::Parameters::Bn254X5,
+        solana_poseidon::Endianness::BigEndian,
+        &[&chunk, &len],
+    )
+    .unwrap()
+    .to_bytes();
+    assert_eq!(WinternitzPoseidon::hash(MESSAGE), expected);
+
+    // Messages longer than one syscall's worth of elements chain the state.
+    let long = [0xffu8; 31 * 30];
+    assert_eq!(
+        WinternitzPoseidon::hashv(&[&long[..100], &long[100..]]),
+        WinternitzPoseidon::hash(&long)
+    );
+    assert_ne!(WinternitzPoseidon::hash(&long), WinternitzPoseidon::hash(&long[1..]));
+    assert_ne!(WinternitzPoseidon::hash(&[]), WinternitzPoseidon::hash(&[0]));
+}
+
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+macro_rules! digest_algorithm {
+    ($name:ident, $digest:ty, $id:expr, $label:expr) => {
+        struct $name;
+
+        impl WinternitzDigestAlgorithm for $name {
+            type Digest = $digest;
+            const ID: u8 = $id;
+            const NAME: &'static str = $label;
+            const DOMAIN: &'static [u8] = concat!("winternitz-", $label).as_bytes();
+        }
+    };
+}
+
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+digest_algorithm!(DigestKeccak, sha3::Keccak256, 0x80, "rc-keccak");
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+digest_algorithm!(DigestSha3, sha3::Sha3_256, 0x81, "rc-sha3");
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+digest_algorithm!(DigestSha512_256, sha2::Sha512_256, 0x82, "rc-sha512-256");
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+digest_algorithm!(DigestSha256, sha2::Sha256, 0x83, "rc-sha256");
+
+#[cfg(all(feature = "sha2", feature = "sha3"))]
+#[test]
+fn test_digest_adapter() {
+    assert_eq!(roundtrip::<WinternitzDigest<DigestKeccak>>().0, ADDRESS_BYTES);
+    assert_eq!(
+        WinternitzDigest::<DigestSha3>::hashv(&[b"te", b"st"]),
+        WinternitzSha3::hash(MESSAGE)
+    );
+
+    // Adapted digests are told apart by their IDs, names and domain tags.
+    type Sha256 = WinternitzDigest<DigestSha256>;
+    type Keccak = WinternitzDigest<DigestKeccak>;
+    assert_eq!((Sha256::NAME, Keccak::NAME), ("rc-sha256", "rc-keccak"));
+    assert_ne!(Sha256::DOMAIN, Keccak::DOMAIN);
+    let bytes = WinternitzAddress::from(ADDRESS_BYTES).to_tagged_bytes::<Sha256>();
+    assert_eq!(bytes[0], 0x83);
+    assert!(WinternitzAddress::from_tagged_bytes::<Sha256>(&bytes).is_ok());
+    assert_eq!(
+        WinternitzAddress::from_tagged_bytes::<Keccak>(&bytes),
+        Err(WinternitzError::HashMismatch { expected: 0x80, found: 0x83 })
+    );
+}
+
+#[test]
+fn test_tagged_serialization() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let bytes = address.to_tagged_bytes::<WinternitzKeccak>();
+    assert_eq!(bytes[0], WinternitzKeccak::ID);
+    assert_eq!(
+        WinternitzAddress::from_tagged_bytes::<WinternitzKeccak>(&bytes).unwrap().0,
+        ADDRESS_BYTES
+    );
+    assert_eq!(
+        WinternitzAddress::from_tagged_bytes::<WinternitzSha256>(&bytes).unwrap_err(),
+        WinternitzError::HashMismatch {
+            expected: WinternitzSha256::ID,
+            found: WinternitzKeccak::ID
+        }
+    );
+    assert_eq!(
+        WinternitzAddress::from_tagged_bytes::<WinternitzKeccak>(&bytes[1..]).unwrap_err(),
+        WinternitzError::InvalidLength
+    );
+
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+    let bytes = signature.to_tagged_bytes::<WinternitzKeccak>();
+    assert!(WinternitzSignature::from_tagged_bytes::<WinternitzKeccak>(&bytes).unwrap() == signature);
+    assert!(WinternitzSignature::from_tagged_bytes::<WinternitzSha256>(&bytes).is_err());
+
+    let (_, commitment, execute) = signature.split::<WinternitzKeccak>(MESSAGE);
+    let bytes = commitment.to_tagged_bytes::<WinternitzKeccak>();
+    assert!(WinternitzCommitmentSignature::from_tagged_bytes::<WinternitzKeccak>(&bytes).unwrap() == commitment);
+    let bytes = execute.to_tagged_bytes::<WinternitzKeccak>();
+    assert!(WinternitzExecuteSignature::from_tagged_bytes::<WinternitzSha256>(&bytes).is_err());
+}
+
+#[test]
+fn test_typed_keys() {
+    let privkey = TypedPrivkey::<WinternitzKeccak>::from_untyped(PRIVKEY);
+    let pubkey = privkey.pubkey();
+    assert_eq!(pubkey.address().untyped().0, ADDRESS_BYTES);
+
+    let signature = privkey.sign(MESSAGE);
+    assert!(signature.verify(MESSAGE, &pubkey));
+    assert!(!signature.verify(b"tesu", &pubkey));
+
+    let (pairing_hash, commitment, execute) = signature.split(MESSAGE);
+    let state_hash = WinternitzKeccak::hash(MESSAGE);
+    assert!(commitment.recover_address(&state_hash, &pairing_hash) == pubkey.address());
+    assert_eq!(execute.recover_pairing_hash(MESSAGE), pairing_hash);
+
+    // Tagged bytes from one algorithm cannot be loaded as another.
+    let bytes = signature.to_tagged_bytes();
+    assert!(TypedSignature::<WinternitzKeccak>::from_tagged_bytes(&bytes).unwrap() == signature);
+    assert!(TypedSignature::<WinternitzSha256>::from_tagged_bytes(&bytes).is_err());
+
+    let privkey = privkey.with_hash::<WinternitzSha256>();
+    assert!(privkey.pubkey().untyped() == &PRIVKEY.pubkey::<WinternitzSha256>());
+}
+
+#[test]
+fn test_merkle() {
+    let leaves: [[u8; 32]; 33] = core::array::from_fn(|i| WinternitzKeccak::hash(&[i as u8]));
+    let h = |a: &[u8; 32], b: &[u8; 32]| WinternitzKeccak::hash_pair(a, b);
+
+    assert_eq!(merkle::root::<WinternitzKeccak>(&leaves[..1]), leaves[0]);
+    assert_eq!(
+        merkle::root::<WinternitzKeccak>(&leaves[..4]),
+        h(&h(&leaves[0], &leaves[1]), &h(&leaves[2], &leaves[3]))
+    );
+    assert_eq!(
+        merkle::root::<WinternitzKeccak>(&leaves[..3]),
+        h(&h(&leaves[0], &leaves[1]), &leaves[2])
+    );
+    assert_eq!(
+        PUBKEY.merklize::<WinternitzKeccak>(),
+        merkle::root::<WinternitzKeccak>(&PUBKEY.0)
+    );
+
+    let mut path = [[0u8; 32]; 6];
+    for n in 1..=leaves.len() {
+        let root = merkle::root::<WinternitzKeccak>(&leaves[..n]);
+        for i in 0..n {
+            let len = merkle::path::<WinternitzKeccak>(&leaves[..n], i, &mut path);
+            assert_eq!(len, merkle::path_len(n, i));
+            let path = &path[..len];
+            assert!(merkle::verify::<WinternitzKeccak>(&root, &leaves[i], i, n, path));
+            assert!(!merkle::verify::<WinternitzKeccak>(&root, &leaves[(i + 1) % 33], i, n, path));
+            assert!(merkle::root_from_path::<WinternitzKeccak>(&leaves[i], n, n, path).is_none());
+        }
+    }
+}
+
+#[test]
+fn test_chain_proof() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+    for index in [0u8, 13, 31] {
+        let proof = PUBKEY.chain_proof::<WinternitzKeccak>(index).unwrap();
+        assert!(proof.verify::<WinternitzKeccak>(&PUBKEY.0[index as usize], &address));
+        assert!(!proof.verify::<WinternitzKeccak>(&PUBKEY.0[(index as usize + 1) % 32], &address));
+
+        let chain = signature.recover_chain::<WinternitzKeccak>(MESSAGE, index).unwrap();
+        assert!(proof.verify::<WinternitzKeccak>(&chain, &address));
+    }
+
+    assert!(PUBKEY.chain_proof::<WinternitzKeccak>(32).is_none());
+    assert!(signature.recover_chain::<WinternitzKeccak>(MESSAGE, 32).is_none());
+
+    let mut proof = PUBKEY.chain_proof::<WinternitzKeccak>(0).unwrap();
+    proof.index = 32;
+    assert!(proof.recover_address::<WinternitzKeccak>(&PUBKEY.0[0]).is_none());
+}
+
+#[test]
+fn test_multi_chain_proof() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+
+    // Neighbouring chains share every node above their common parent.
+    let proof = PUBKEY.multi_chain_proof::<WinternitzKeccak>(&[4, 5]).unwrap();
+    assert_eq!(proof.mask, 0b110000);
+    assert_eq!(proof.nodes.len(), 4);
+    assert!(proof.verify::<WinternitzKeccak>(&[PUBKEY.0[4], PUBKEY.0[5]], &address));
+    assert!(!proof.verify::<WinternitzKeccak>(&[PUBKEY.0[5], PUBKEY.0[4]], &address));
+    assert!(!proof.verify::<WinternitzKeccak>(&[PUBKEY.0[4]], &address));
+
+    // Unordered and duplicate indices select the same chains.
+    let indices = [31u8, 0, 13, 14, 13];
+    let proof = PUBKEY.multi_chain_proof::<WinternitzKeccak>(&indices).unwrap();
+    assert!(proof.nodes.len() < 5 * 4);
+    let chains: std::vec::Vec<[u8; 32]> = [0u8, 13, 14, 31]
+        .iter()
+        .map(|i| signature.recover_chain::<WinternitzKeccak>(MESSAGE, *i).unwrap())
+        .collect();
+    assert!(proof.verify::<WinternitzKeccak>(&chains, &address));
+
+    // Every chain needs no nodes at all, a single chain a full path.
+    let all: std::vec::Vec<u8> = (0..32).collect();
+    let proof = PUBKEY.multi_chain_proof::<WinternitzKeccak>(&all).unwrap();
+    assert!(proof.nodes.is_empty());
+    assert!(proof.verify::<WinternitzKeccak>(&PUBKEY.0, &address));
+    let proof = PUBKEY.multi_chain_proof::<WinternitzKeccak>(&[9]).unwrap();
+    assert_eq!(proof.nodes, PUBKEY.chain_proof::<WinternitzKeccak>(9).unwrap().path);
+
+    let mut extra = proof.clone();
+    extra.nodes.push([0u8; 32]);
+    assert!(extra.recover_address::<WinternitzKeccak>(&[PUBKEY.0[9]]).is_none());
+    assert!(PUBKEY.multi_chain_proof::<WinternitzKeccak>(&[]).is_none());
+    assert!(PUBKEY.multi_chain_proof::<WinternitzKeccak>(&[3, 32]).is_none());
+}
+
+#[test]
+fn test_recover_address() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+    assert!(signature.recover_address::<WinternitzKeccak>(MESSAGE) == address);
+    assert!(signature.verify_address::<WinternitzKeccak>(MESSAGE, &address));
+    assert!(!signature.verify_address::<WinternitzKeccak>(b"tesu", &address));
+    assert!(!signature.verify_address::<WinternitzSha256>(MESSAGE, &address));
+
+    let leaves: [[u8; 32]; 33] = core::array::from_fn(|i| WinternitzKeccak::hash(&[i as u8]));
+    for n in 1..=leaves.len() {
+        let mut tree = merkle::MerkleStream::<WinternitzKeccak, 6>::new();
+        for leaf in leaves[..n].iter() {
+            tree.push(*leaf);
+        }
+        assert_eq!(tree.finalize(), merkle::root::<WinternitzKeccak>(&leaves[..n]));
+    }
+}
+
+std::thread_local! {
+    static HASHES: core::cell::Cell<[u32; 3]> = const { core::cell::Cell::new([0; 3]) };
+}
+
+/// Keccak that counts single 32-byte hashes, pair hashes and other hashes.
+struct CountingKeccak;
+
+impl CountingKeccak {
+    fn count(kind: usize) {
+        HASHES.with(|c| {
+            let mut counts = c.get();
+            counts[kind] += 1;
+            c.set(counts)
+        });
+    }
+
+    fn take() -> [u32; 3] {
+        HASHES.with(|c| c.replace([0; 3]))
+    }
+}
+
+impl WinternitzHash for CountingKeccak {
+    const ID: u8 = WinternitzKeccak::ID;
+    const NAME: &'static str = "counting-keccak";
+    const DOMAIN: &'static [u8] = WinternitzKeccak::DOMAIN;
+
+    fn hash(msg: &[u8]) -> [u8; 32] {
+        Self::count(if msg.len() == 32 { 0 } else { 2 });
+        WinternitzKeccak::hash(msg)
+    }
+
+    fn hashd(msg: &[u8]) -> [u8; 32] {
+        Self::hash(&Self::hash(msg))
+    }
+
+    fn hashv(msg: &[&[u8]]) -> [u8; 32] {
+        Self::count(2);
+        WinternitzKeccak::hashv(msg)
+    }
+
+    fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+        Self::count(1);
+        WinternitzKeccak::hash_pair(a, b)
+    }
+}
+
+#[test]
+fn test_verification_cost() {
+    let signature = PRIVKEY.sign::<CountingKeccak>(MESSAGE);
+    let (pairing_hash, commitment, execute) = signature.split::<CountingKeccak>(MESSAGE);
+    let state_hash = CountingKeccak::hash(MESSAGE);
+    let estimate = cost::<CountingKeccak>(MESSAGE);
+    CountingKeccak::take();
+
+    signature.recover_address::<CountingKeccak>(MESSAGE);
+    let full = estimate.full;
+    assert_eq!(
+        CountingKeccak::take(),
+        [full.chain_hashes + full.digest_hashes, full.tree_hashes, full.message_hashes]
+    );
+
+    commitment.recover_address::<CountingKeccak>(&state_hash, &pairing_hash);
+    let c = estimate.commitment;
+    assert_eq!(CountingKeccak::take(), [c.chain_hashes + c.digest_hashes, c.tree_hashes, 0]);
+
+    execute.recover_pairing_hash::<CountingKeccak>(MESSAGE);
+    let e = estimate.execute;
+    assert_eq!(
+        CountingKeccak::take(),
+        [e.chain_hashes + e.digest_hashes, e.tree_hashes, e.message_hashes]
+    );
+
+    let digest = WinternitzKeccak::hashd(MESSAGE);
+    assert_eq!(full.chain_hashes, digest.iter().map(|v| *v as u32).sum::<u32>());
+    assert_eq!(full.chain_hashes, c.chain_hashes + e.chain_hashes);
+    assert_eq!(
+        full.compute_units::<WinternitzKeccak>(),
+        (full.chain_hashes as u64 + 2) * 101 + 31 * 117 + 95
+    );
+    assert_eq!(WinternitzSha256::hash_cu(&[32]), 101);
+}
+
+#[test]
+fn test_grind() {
+    let (signature, nonce) = PRIVKEY
+        .sign_ground::<WinternitzKeccak>(MESSAGE, 3600, 10_000)
+        .unwrap();
+    assert!(signature.recover_pubkey_with_nonce::<WinternitzKeccak>(MESSAGE, nonce) == PUBKEY);
+    assert_eq!(grind::<WinternitzKeccak>(MESSAGE, 3600, 10_000), Some(nonce));
+
+    // A ground signature is a plain signature over `message || nonce`.
+    let mut message = MESSAGE.to_vec();
+    message.extend_from_slice(&nonce.to_le_bytes());
+    assert!(signature.recover_pubkey::<WinternitzKeccak>(&message) == PUBKEY);
+    assert!(cost::<WinternitzKeccak>(&message).full.chain_hashes <= 3600);
+
+    let (pairing_hash, commitment, execute) = signature.split::<WinternitzKeccak>(&message);
+    let state_hash = WinternitzKeccak::hash(&message);
+    let address = commitment.recover_address::<WinternitzKeccak>(&state_hash, &pairing_hash);
+    assert_eq!(address.0, ADDRESS_BYTES);
+    assert_eq!(execute.recover_pairing_hash::<WinternitzKeccak>(&message), pairing_hash);
+
+    assert!(PRIVKEY.sign_ground::<WinternitzKeccak>(MESSAGE, 0, 100).is_none());
+}
+
+#[test]
+fn test_target_sum() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let signature = PRIVKEY.sign_target_sum::<WinternitzKeccak>(MESSAGE);
+    let v = target_sum_digest::<WinternitzKeccak>(MESSAGE, &signature.randomizer);
+    assert_eq!(v.iter().map(|v| *v as u32).sum::<u32>(), TARGET_SUM);
+
+    assert!(signature.recover_pubkey::<WinternitzKeccak>(MESSAGE).unwrap() == PUBKEY);
+    assert!(signature.verify_address::<WinternitzKeccak>(MESSAGE, &address));
+    assert!(!signature.verify_address::<WinternitzKeccak>(b"tesu", &address));
+
+    let state_hash = target_sum_state_hash::<WinternitzKeccak>(MESSAGE, &signature.randomizer);
+    let (pairing_hash, commitment, execute) =
+        signature.split::<WinternitzKeccak>(MESSAGE).unwrap();
+    assert_eq!(
+        execute.recover_pairing_hash_target_sum::<WinternitzKeccak>(MESSAGE, &signature.randomizer),
+        Some(pairing_hash)
+    );
+    assert_eq!(
+        commitment.recover_address_target_sum::<WinternitzKeccak>(&state_hash, &pairing_hash),
+        Some(address)
+    );
+
+    // Flipping the randomizer's top byte lands on a digest that misses the
+    // target sum, so every recovery path rejects it.
+    let mut forged = PRIVKEY.sign_target_sum::<WinternitzKeccak>(MESSAGE);
+    forged.randomizer[7] ^= 1;
+    let v = target_sum_digest::<WinternitzKeccak>(MESSAGE, &forged.randomizer);
+    assert_ne!(v.iter().map(|v| *v as u32).sum::<u32>(), TARGET_SUM);
+    assert!(forged.recover_pubkey::<WinternitzKeccak>(MESSAGE).is_none());
+    assert!(forged.split::<WinternitzKeccak>(MESSAGE).is_none());
+    let state_hash = target_sum_state_hash::<WinternitzKeccak>(MESSAGE, &forged.randomizer);
+    assert!(commitment
+        .recover_address_target_sum::<WinternitzKeccak>(&state_hash, &pairing_hash)
+        .is_none());
+}
+
+fn truncated_roundtrip<const N: usize>() {
+    let privkey = WinternitzTruncatedPrivkey::<N>::from(&PRIVKEY);
+    let address = privkey.pubkey::<WinternitzKeccak>().address::<WinternitzKeccak>();
+    let signature = privkey.sign::<WinternitzKeccak>(MESSAGE);
+    assert_eq!(core::mem::size_of_val(&signature), 32 * N);
+    assert!(signature.verify_address::<WinternitzKeccak>(MESSAGE, &address));
+    assert!(!signature.verify_address::<WinternitzKeccak>(b"tesu", &address));
+}
+
+#[test]
+fn test_truncated_widths() {
+    truncated_roundtrip::<16>();
+    truncated_roundtrip::<20>();
+    truncated_roundtrip::<24>();
+
+    // At full width the truncated scheme is the regular one.
+    let privkey = WinternitzTruncatedPrivkey::<32>::from(&PRIVKEY);
+    assert!(privkey.pubkey::<WinternitzKeccak>().0 == PUBKEY.0);
+    let address = privkey.pubkey::<WinternitzKeccak>().address::<WinternitzKeccak>();
+    assert_eq!(address.0, ADDRESS_BYTES);
+
+    // Chains are truncated at every step, not just at the end.
+    let privkey = WinternitzPrivkey16::from(&PRIVKEY);
+    assert_ne!(privkey.pubkey::<WinternitzKeccak>().0[0][..], PUBKEY.0[0][..16]);
+}
+
+#[test]
+fn test_salted_signature() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let salted = PRIVKEY.sign_randomized::<WinternitzKeccak>(MESSAGE);
+    assert!(salted.recover_pubkey::<WinternitzKeccak>(MESSAGE) == PUBKEY);
+    assert!(salted.verify_address::<WinternitzKeccak>(MESSAGE, &address));
+    assert!(!salted.verify_address::<WinternitzKeccak>(b"tesu", &address));
+
+    // The salt changes the digest, so the signature differs from `sign`.
+    assert!(salted.signature != PRIVKEY.sign::<WinternitzKeccak>(MESSAGE));
+    let other = PRIVKEY.sign_salted::<WinternitzKeccak>(MESSAGE, &[0u8; 32]);
+    let recovered = other
+        .signature
+        .recover_pubkey_salted::<WinternitzKeccak>(MESSAGE, &salted.salt);
+    assert!(recovered != PUBKEY);
+
+    let (pairing_hash, commitment, execute) = salted.split::<WinternitzKeccak>(MESSAGE);
+    let recovered =
+        commitment.recover_address_salted::<WinternitzKeccak>(MESSAGE, &salted.salt, &pairing_hash);
+    assert!(recovered == address);
+    let state_hash = salted_state_hash::<WinternitzKeccak>(MESSAGE, &salted.salt);
+    assert!(commitment.recover_address::<WinternitzKeccak>(&state_hash, &pairing_hash) == address);
+    assert_eq!(
+        execute.recover_pairing_hash_salted::<WinternitzKeccak>(MESSAGE, &salted.salt),
+        pairing_hash
+    );
+}
+
+#[allow(dead_code)]
+fn streaming_matches<H: WinternitzStreamingHash>() {
+    let message = [0x5au8; 1000];
+    let mut hasher = WinternitzMessageHasher::<H>::new();
+    for chunk in message.chunks(33) {
+        hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize(), H::hashd(&message));
+
+    let mut hasher = WinternitzMessageHasher::<H>::new();
+    hasher.update(&message[..10]).update(&message[10..]);
+    assert_eq!(hasher.finalize_state_hash(), H::hash(&message));
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_prehashed_signing() {
+    streaming_matches::<WinternitzKeccak>();
+    streaming_matches::<WinternitzSha3>();
+    #[cfg(feature = "sha2")]
+    streaming_matches::<WinternitzSha256>();
+    #[cfg(feature = "blake3")]
+    streaming_matches::<WinternitzBlake3>();
+    #[cfg(feature = "poseidon")]
+    streaming_matches::<WinternitzPoseidon>();
+    #[cfg(feature = "sha2")]
+    streaming_matches::<WinternitzDigest<DigestSha512_256>>();
+
+    let mut hasher = WinternitzMessageHasher::<WinternitzKeccak>::new();
+    hasher.update(b"te").update(b"st");
+    let digest = hasher.finalize();
+
+    let signature = PRIVKEY.sign_prehashed::<WinternitzKeccak>(&digest);
+    assert!(signature == PRIVKEY.sign::<WinternitzKeccak>(MESSAGE));
+    assert!(signature.recover_pubkey_prehashed::<WinternitzKeccak>(&digest) == PUBKEY);
+    assert_eq!(
+        signature.recover_address_prehashed::<WinternitzKeccak>(&digest).0,
+        ADDRESS_BYTES
+    );
+
+    let (pairing_hash, _, execute) = signature.split_prehashed::<WinternitzKeccak>(&digest);
+    assert_eq!(
+        execute.recover_pairing_hash_prehashed::<WinternitzKeccak>(&digest),
+        pairing_hash
+    );
+}
+
+#[test]
+fn test_batch_signing() {
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let messages: [&[u8]; 5] = [b"a", b"b", b"c", b"d", b"e"];
+    let (signature, proofs) = PRIVKEY.sign_batch::<WinternitzKeccak>(&messages).unwrap();
+    assert_eq!(proofs.len(), messages.len());
+
+    for (message, proof) in messages.iter().zip(proofs.iter()) {
+        assert!(proof.verify::<WinternitzKeccak>(&signature, message, &address));
+        assert!(!proof.verify::<WinternitzKeccak>(&signature, b"f", &address));
+    }
+    // Proofs are bound to their index and the batch size.
+    assert!(!proofs[1].verify::<WinternitzKeccak>(&signature, b"a", &address));
+    let mut proof = proofs[4].clone();
+    proof.count = 6;
+    assert!(!proof.verify::<WinternitzKeccak>(&signature, b"e", &address));
+
+    let (signature, proofs) = PRIVKEY.sign_batch::<WinternitzKeccak>(&[MESSAGE]).unwrap();
+    assert!(proofs[0].path.is_empty());
+    assert!(proofs[0].verify::<WinternitzKeccak>(&signature, MESSAGE, &address));
+
+    // The signed message is domain separated from the raw `count || root`.
+    let root = batch_leaf::<WinternitzKeccak>(MESSAGE);
+    let message = batch_message::<WinternitzKeccak>(&root, 1);
+    assert!(signature.verify_address::<WinternitzKeccak>(&message, &address));
+    let raw = [&1u32.to_le_bytes()[..], &root[..]].concat();
+    assert!(!signature.verify_address::<WinternitzKeccak>(&raw, &address));
+
+    let empty = PRIVKEY.sign_batch::<WinternitzKeccak>(&[]);
+    assert_eq!(empty.err(), Some(WinternitzError::EmptyBatch));
+
+    // Level-by-level proofs match `merkle::path` for every tree shape.
+    let messages: std::vec::Vec<std::vec::Vec<u8>> = (0u8..13).map(|i| std::vec![i]).collect();
+    for n in 1..=messages.len() {
+        let batch: std::vec::Vec<&[u8]> = messages[..n].iter().map(|m| m.as_slice()).collect();
+        let leaves: std::vec::Vec<[u8; 32]> =
+            batch.iter().map(|m| batch_leaf::<WinternitzKeccak>(m)).collect();
+        let (root, proofs) = batch_tree::<WinternitzKeccak>(&batch).unwrap();
+        assert_eq!(root, merkle::root::<WinternitzKeccak>(&leaves));
+        for (index, proof) in proofs.iter().enumerate() {
+            let mut path = std::vec![[0u8; 32]; merkle::path_len(n, index)];
+            merkle::path::<WinternitzKeccak>(&leaves, index, &mut path);
+            assert_eq!(proof.path, path);
+        }
+    }
+}
+
+#[test]
+fn test_one_time_signing_key() {
+    let key =
+        OneTimeSigningKey::<WinternitzKeccak>::from_untyped(WinternitzPrivkey::from(PRIVKEY.0));
+    let address = key.address();
+    assert_eq!(address.untyped(), &WinternitzAddress::from(ADDRESS_BYTES));
+
+    let (signature, used) = key.sign(MESSAGE);
+    assert_eq!(used.address(), address);
+    assert!(signature.verify(MESSAGE, used.pubkey()));
+    assert_eq!(signature.untyped(), &PRIVKEY.sign::<WinternitzKeccak>(MESSAGE));
+}
+
+#[test]
+fn test_reuse_analysis() {
+    let other: &[u8] = b"a second message";
+    let first = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+    let second = PRIVKEY.sign::<WinternitzKeccak>(other);
+    let analysis =
+        WinternitzReuseAnalysis::analyze::<WinternitzKeccak>(&[(&first, MESSAGE), (&second, other)])
+            .unwrap();
+    assert_eq!(analysis.pubkey, PUBKEY);
+
+    let (a, b) = (WinternitzKeccak::hashd(MESSAGE), WinternitzKeccak::hashd(other));
+    let max: [u8; 32] = core::array::from_fn(|i| a[i].max(b[i]));
+    assert_eq!(analysis.max_digits(), max);
+    assert!(analysis.is_forgeable::<WinternitzKeccak>(MESSAGE));
+    assert!(analysis.forgeable_log2() < 0.0);
+
+    // Mixing the two digests yields a fresh forgeable digest.
+    let mixed: [u8; 32] = core::array::from_fn(|i| if i % 2 == 0 { a[i] } else { b[i] });
+    let forged = analysis.forge_prehashed::<WinternitzKeccak>(&mixed).unwrap();
+    assert_eq!(forged.recover_pubkey_prehashed::<WinternitzKeccak>(&mixed), PUBKEY);
+    assert!(analysis.forge_prehashed::<WinternitzKeccak>(&[255u8; 32]).is_none());
+
+    let stranger = WinternitzPrivkey::generate().sign::<WinternitzKeccak>(other);
+    let pairs: [(&WinternitzSignature, &[u8]); 2] = [(&first, MESSAGE), (&stranger, other)];
+    assert_eq!(
+        WinternitzReuseAnalysis::analyze::<WinternitzKeccak>(&pairs).err(),
+        Some(WinternitzError::PubkeyMismatch { index: 1 })
+    );
+    assert_eq!(
+        WinternitzReuseAnalysis::analyze::<WinternitzKeccak>(&[]).err(),
+        Some(WinternitzError::InvalidLength)
+    );
+}
+
+#[test]
+fn test_cached_signer() {
+    let other: &[u8] = b"another message";
+    for interval in [1, 7, 16, 100, 256] {
+        let signer = WinternitzCachedSigner::<WinternitzKeccak>::new(&PRIVKEY, interval);
+        assert_eq!(signer.pubkey(), &PUBKEY);
+        assert_eq!(signer.address(), WinternitzAddress::from(ADDRESS_BYTES));
+        assert_eq!(signer.sign(MESSAGE), PRIVKEY.sign::<WinternitzKeccak>(MESSAGE));
+        assert_eq!(signer.sign(other), PRIVKEY.sign::<WinternitzKeccak>(other));
+        for digest in [[0u8; 32], [255u8; 32]] {
+            assert_eq!(
+                signer.sign_prehashed(&digest),
+                PRIVKEY.sign_prehashed::<WinternitzKeccak>(&digest)
+            );
+        }
+    }
+
+    let budget = WinternitzCachedSigner::<WinternitzKeccak>::memory_for(16);
+    let signer =
+        WinternitzCachedSigner::<WinternitzKeccak>::with_memory(&PRIVKEY, budget).unwrap();
+    assert_eq!(signer.interval(), 16);
+    assert!(signer.memory() <= budget);
+
+    let smallest = WinternitzCachedSigner::<WinternitzKeccak>::memory_for(256);
+    assert!(WinternitzCachedSigner::<WinternitzKeccak>::with_memory(&PRIVKEY, smallest).is_some());
+    assert!(
+        WinternitzCachedSigner::<WinternitzKeccak>::with_memory(&PRIVKEY, smallest - 1).is_none()
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_matches_serial() {
+    use crate::parallel;
+
+    assert_eq!(PRIVKEY.par_pubkey::<WinternitzKeccak>(), PUBKEY);
+    let signature = PRIVKEY.sign::<WinternitzKeccak>(MESSAGE);
+    assert_eq!(signature.par_recover_pubkey::<WinternitzKeccak>(MESSAGE), PUBKEY);
+
+    let seed = [7u8; 64];
+    let paths: [&str; 4] = ["m/44'/501'/0'/0'", "m/44'/501'/1'/0'", "m/44'/501'/2'/0'", "m/0"];
+    let privkeys: std::vec::Vec<WinternitzPrivkey> = paths
+        .iter()
+        .map(|path| WinternitzPrivkey::from_seed(seed, path).unwrap())
+        .collect();
+    let serial: std::vec::Vec<WinternitzAddress> = privkeys
+        .iter()
+        .map(|privkey| privkey.pubkey::<WinternitzSha256>().address::<WinternitzSha256>())
+        .collect();
+    assert_eq!(parallel::addresses::<WinternitzSha256>(&privkeys), serial);
+    assert_eq!(parallel::addresses_from_seed::<WinternitzSha256, _>(seed, &paths).unwrap(), serial);
+    assert!(parallel::addresses_from_seed::<WinternitzSha256, _>(seed, &["bad"]).is_err());
+    // The error is always that of the earliest invalid path.
+    let mut paths = std::vec!["m/0"; 16];
+    paths[3] = "m/x";
+    paths[4..].fill("bad");
+    assert_eq!(
+        parallel::addresses_from_seed::<WinternitzSha256, _>(seed, &paths),
+        Err(bip32::Error::ChildNumber)
+    );
+
+    let address = WinternitzAddress::from(ADDRESS_BYTES);
+    let items: [(&WinternitzSignature, &[u8], &WinternitzAddress); 2] =
+        [(&signature, MESSAGE, &address), (&signature, b"other", &address)];
+    assert_eq!(parallel::verify_many::<WinternitzKeccak>(&items), [true, false]);
+}
+
+#[test]
+fn test_multibuf_matches_serial() {
+    fn check<H: WinternitzHash>(walk: fn(&mut [[u8; 32]], &[u8])) {
+        let steps: [u8; 32] = core::array::from_fn(|i| (i * 37 % 256) as u8);
+        let mut chains = PRIVKEY.0;
+        walk(&mut chains, &steps);
+        for (i, chain) in chains.iter().enumerate() {
+            let mut expected = PRIVKEY.0[i];
+            for _ in 0..steps[i] {
+                expected = H::hash(&expected);
+            }
+            assert_eq!(*chain, expected, "{} chain {}", H::NAME, i);
+        }
+
+        // Fewer chains than lanes, and chains that do not move.
+        let mut chains = [PRIVKEY.0[0], PRIVKEY.0[1], PRIVKEY.0[2]];
+        walk(&mut chains, &[0, 3, 1]);
+        assert_eq!(chains[0], PRIVKEY.0[0]);
+        assert_eq!(chains[2], H::hash(&PRIVKEY.0[2]));
+    }
+    check::<WinternitzKeccak>(multibuf::keccak_walk_chains);
+    check::<WinternitzSha256>(multibuf::sha256_walk_chains);
+
+    // Every backend this CPU can run, not just the one dispatch picks.
+    check::<WinternitzKeccak>(multibuf::keccak_walk_chains_serial);
+    check::<WinternitzSha256>(multibuf::sha256_walk_chains_serial);
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") {
+        check::<WinternitzKeccak>(|c, s| unsafe { multibuf::keccak_walk_chains_avx2(c, s) });
+        check::<WinternitzSha256>(|c, s| unsafe { multibuf::sha256_walk_chains_avx2(c, s) });
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        check::<WinternitzKeccak>(multibuf::keccak_walk_chains_neon);
+        check::<WinternitzSha256>(multibuf::sha256_walk_chains_neon);
+    }
+
+    let signature = PRIVKEY.sign::<WinternitzSha256>(MESSAGE);
+    let pubkey = PRIVKEY.pubkey::<WinternitzSha256>();
+    assert_eq!(signature.recover_pubkey::<WinternitzSha256>(MESSAGE), pubkey);
+    assert_eq!(
+        pubkey.address::<WinternitzSha256>(),
+        signature.recover_address::<WinternitzSha256>(MESSAGE)
+    );
+}
+
+#[test]
+fn test_address_pool() {
+    let seed = [9u8; 64];
+    let template = "m/44'/501'/{index}'/0'";
+    let pool = WinternitzAddressPool::derive::<WinternitzKeccak>(seed, template, 5..8).unwrap();
+    assert_eq!(pool.entries.len(), 3);
+    assert_eq!(pool.entries[1].index, 6);
+    assert_eq!(pool.entries[1].path, "m/44'/501'/6'/0'");
+    let privkey = WinternitzPrivkey::from_seed(seed, "m/44'/501'/6'/0'").unwrap();
+    let address = privkey.pubkey::<WinternitzKeccak>().address::<WinternitzKeccak>();
+    assert_eq!(pool.find(&address), Some(&pool.entries[1]));
+
+    let file = pool.to_watch_only();
+    assert!(file.starts_with("winternitz-pool v1\nhash keccak 0\n"));
+    let loaded = WinternitzAddressPool::from_watch_only(&file).unwrap();
+    assert_eq!(loaded, pool);
+    assert_eq!(loaded.verify::<WinternitzKeccak>(seed), Ok(()));
+
+    assert_eq!(
+        loaded.verify::<WinternitzKeccak>([8u8; 64]),
+        Err(WinternitzError::AddressMismatch { index: 5 })
+    );
+    assert_eq!(
+        loaded.verify::<WinternitzSha256>(seed),
+        Err(WinternitzError::HashMismatch { expected: 1, found: 0 })
+    );
+    let mut tampered = loaded.clone();
+    tampered.entries[2].address = address;
+    assert_eq!(
+        tampered.verify::<WinternitzKeccak>(seed),
+        Err(WinternitzError::AddressMismatch { index: 7 })
+    );
+
+    assert_eq!(
+        WinternitzAddressPool::from_watch_only(&file.replace("6 m/", "x m/")),
+        Err(WinternitzError::InvalidPoolFile { line: 5 })
+    );
+    // Entries must match the template on load.
+    assert_eq!(
+        WinternitzAddressPool::from_watch_only(&file.replace("6 m/44'/501'/6'", "6 m/44'/501'/9'")),
+        Err(WinternitzError::InvalidPoolFile { line: 5 })
+    );
+    assert_eq!(
+        WinternitzAddressPool::from_watch_only(&file.replace("{index}", "5")),
+        Err(WinternitzError::InvalidPoolFile { line: 3 })
+    );
+    assert_eq!(
+        WinternitzAddressPool::derive::<WinternitzKeccak>(seed, "m/44'/501'/0'", 0..1),
+        Err(WinternitzError::InvalidPath)
+    );
+}
+
+#[test]
+fn test_watch_wallet() {
+    let seed = [3u8; 64];
+    let template = "m/44'/501'/{index}'/0'";
+    let pool = WinternitzAddressPool::derive::<WinternitzKeccak>(seed, template, 0..3).unwrap();
+    let mut wallet = WinternitzWatchWallet::<WinternitzKeccak>::new();
+    wallet
+        .load_watch_only(&pool.to_watch_only())
+        .unwrap();
+    assert_eq!(wallet.len(), 3);
+    assert_eq!(
+        wallet.load_pool(&WinternitzAddressPool { hash: 1, ..pool.clone() }),
+        Err(WinternitzError::HashMismatch { expected: 0, found: 1 })
+    );
+
+    let privkey = WinternitzPrivkey::from_seed(seed, &pool.entries[1].path).unwrap();
+    let address = pool.entries[1].address;
+    let first = split_message(1, &[1u8; 32], &[2u8; 32]);
+    let second = split_message(2, &[1u8; 32], &[2u8; 32]);
+
+    // Commit then execute.
+    let signature = privkey.sign::<WinternitzKeccak>(&first);
+    let (pairing_hash, commitment, execute) = signature.split::<WinternitzKeccak>(&first);
+    let state_hash = WinternitzKeccak::hash(&first);
+    assert_eq!(
+        wallet.observe_commitment(&commitment, &state_hash, &pairing_hash),
+        WinternitzObservation::Used(address)
+    );
+    assert_eq!(wallet.status(&address), Some(WinternitzAddressStatus::Used));
+    assert_eq!(wallet.observe_execute(&execute, &first), WinternitzObservation::Spent(address));
+    assert_eq!(wallet.status(&address), Some(WinternitzAddressStatus::Spent));
+    assert_eq!(wallet.reused().count(), 0);
+    assert_eq!(wallet.unused().count(), 2);
+
+    // A second message from the same key is flagged.
+    let signature = privkey.sign::<WinternitzKeccak>(&second);
+    let (_, _, execute) = signature.split::<WinternitzKeccak>(&second);
+    assert_eq!(wallet.observe_execute(&execute, &second), WinternitzObservation::Reused(address));
+    assert!(wallet.entry(&address).unwrap().is_reused());
+    assert_eq!(wallet.reused().count(), 1);
+
+    // Full signatures, and signatures from keys outside the pool.
+    let other = WinternitzPrivkey::from_seed(seed, &pool.entries[2].path).unwrap();
+    assert_eq!(
+        wallet.observe_signature(&other.sign::<WinternitzKeccak>(MESSAGE), MESSAGE),
+        WinternitzObservation::Spent(pool.entries[2].address)
+    );
+    assert_eq!(
+        wallet.observe_signature(&PRIVKEY.sign::<WinternitzKeccak>(MESSAGE), MESSAGE),
+        WinternitzObservation::Unknown
+    );
+    assert!(wallet.mark_spent(&pool.entries[0].address));
+    assert_eq!(wallet.unused().count(), 0);
+}
+
+#[test]
+fn test_multisig() {
+    let seed = [5u8; 64];
+    let keys: std::vec::Vec<WinternitzPrivkey> = (0..3)
+        .map(|i| WinternitzPrivkey::from_seed(seed, &std::format!("m/44'/501'/{}'/0'", i)).unwrap())
+        .collect();
+    let members = keys
+        .iter()
+        .map(|key| key.pubkey::<WinternitzKeccak>().address::<WinternitzKeccak>())
+        .collect();
+    let multisig = WinternitzMultisig::new(2, members).unwrap();
+    let address = multisig.address::<WinternitzKeccak>();
+
+    let signatures = std::vec![
+        keys[2].sign::<WinternitzKeccak>(MESSAGE),
+        keys[0].sign::<WinternitzKeccak>(MESSAGE),
+    ];
+    let bundle = multisig.combine::<WinternitzKeccak>(MESSAGE, signatures).unwrap();
+    assert_eq!(bundle.members[0].index, 0);
+    assert_eq!(bundle.members[1].index, 2);
+    assert!(bundle.verify::<WinternitzKeccak>(&address, 2, 3, MESSAGE));
+    assert!(!bundle.verify::<WinternitzKeccak>(&address, 2, 3, b"other"));
+    assert!(!bundle.verify::<WinternitzKeccak>(&address, 1, 3, MESSAGE));
+    assert!(!bundle.verify::<WinternitzSha256>(&address, 2, 3, MESSAGE));
+
+    let member = |i: usize| WinternitzMultisigMemberRef {
+        index: bundle.members[i].index,
+        signature: &bundle.members[i].signature,
+        path: &bundle.members[i].path,
+    };
+    // Below threshold, repeated and out-of-order members are rejected.
+    assert!(!verify_multisig::<WinternitzKeccak>(&address, 2, 3, MESSAGE, &[member(0)]));
+    assert!(!verify_multisig::<WinternitzKeccak>(&address, 2, 3, MESSAGE, &[member(0), member(0)]));
+    assert!(!verify_multisig::<WinternitzKeccak>(&address, 2, 3, MESSAGE, &[member(1), member(0)]));
+    for member in &bundle.members {
+        assert_eq!(member.path, multisig.path::<WinternitzKeccak>(member.index));
+    }
+
+    // A tree listing one key twice cannot count that key twice.
+    let leaves = [multisig.members()[0].0, multisig.members()[0].0, multisig.members()[1].0];
+    let root = merkle::root::<WinternitzKeccak>(&leaves);
+    let address = multisig_address::<WinternitzKeccak>(2, 3, &root);
+    let signature = keys[0].sign::<WinternitzKeccak>(MESSAGE);
+    let paths: std::vec::Vec<std::vec::Vec<[u8; 32]>> = (0..2)
+        .map(|i| {
+            let mut path = std::vec![[0u8; 32]; merkle::path_len(3, i)];
+            merkle::path::<WinternitzKeccak>(&leaves, i, &mut path);
+            path
+        })
+        .collect();
+    let twice = [0u8, 1].map(|i| WinternitzMultisigMemberRef {
+        index: i,
+        signature: &signature,
+        path: &paths[i as usize],
+    });
+    assert!(!verify_multisig::<WinternitzKeccak>(&address, 2, 3, MESSAGE, &twice));
+    let address = multisig_address::<WinternitzKeccak>(1, 3, &root);
+    assert!(verify_multisig::<WinternitzKeccak>(&address, 1, 3, MESSAGE, &twice[..1]));
+
+    // A 1-of-3 with the same members is a different address.
+    let single = WinternitzMultisig::new(1, multisig.members().to_vec()).unwrap();
+    assert_ne!(single.address::<WinternitzKeccak>(), address);
+
+    assert_eq!(
+        multisig.combine::<WinternitzKeccak>(
+            MESSAGE,
+            std::vec![keys[1].sign::<WinternitzKeccak>(MESSAGE)]
+        ),
+        Err(WinternitzError::InvalidMultisig)
+    );
+    assert_eq!(
+        multisig.combine::<WinternitzKeccak>(
+            MESSAGE,
+            std::vec![
+                keys[1].sign::<WinternitzKeccak>(MESSAGE),
+                PRIVKEY.sign::<WinternitzKeccak>(MESSAGE)
+            ]
+        ),
+        Err(WinternitzError::InvalidMultisig)
+    );
+    assert_eq!(
+        WinternitzMultisig::new(2, std::vec![multisig.members()[0], multisig.members()[0]]),
+        Err(WinternitzError::InvalidMultisig)
+    );
+}
+
+#[test]
+fn test_shamir() {
+    let seed: [u8; 64] = core::array::from_fn(|i| i as u8 * 3);
+    let shares = shamir::split_seed(&seed, 3, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+    assert_eq!(shamir::combine_seed(&shares).unwrap(), seed);
+    assert_eq!(shamir::combine_seed(&shares[2..]).unwrap(), seed);
+    let picked = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+    assert_eq!(shamir::combine_seed(&picked).unwrap(), seed);
+    assert_eq!(shamir::combine_seed(&shares[..2]), Err(WinternitzError::NotEnoughShares));
+    let repeated = [shares[0].clone(), shares[0].clone(), shares[1].clone()];
+    assert_eq!(shamir::combine_seed(&repeated), Err(WinternitzError::InvalidShare));
+
+    // Shares of another secret with the same parameters do not mix.
+    let other = shamir::split_seed(&[1u8; 64], 3, 5).unwrap();
+    let mixed = [shares[0].clone(), shares[1].clone(), other[2].clone()];
+    assert_eq!(shamir::combine_seed(&mixed), Err(WinternitzError::InvalidShare));
+    // Even under a matching id, the shared digest rejects the reconstruction.
+    let mut forged = other[2].clone();
+    forged.id = shares[0].id;
+    let mixed = [shares[0].clone(), shares[1].clone(), forged];
+    assert_eq!(shamir::combine_seed(&mixed), Err(WinternitzError::InvalidShare));
+
+    // The id comes from the RNG, not from the secret.
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let seeded = shamir::split_with_rng(&seed, 3, 5, &mut StdRng::seed_from_u64(1)).unwrap();
+    assert_eq!(seeded[0].id, StdRng::seed_from_u64(1).random::<[u8; 4]>());
+    assert_eq!(shamir::combine_seed(&seeded).unwrap(), seed);
+    let reseeded = shamir::split_with_rng(&seed, 3, 5, &mut StdRng::seed_from_u64(1)).unwrap();
+    assert_eq!(reseeded, seeded);
+
+    // Encoding roundtrip and corruption detection.
+    let bytes = shares[1].to_bytes();
+    assert_eq!(bytes.len(), 13 + 64 + 4);
+    assert_eq!(WinternitzShare::from_bytes(&bytes).unwrap(), shares[1]);
+    let mut corrupted = bytes.clone();
+    corrupted[20] ^= 1;
+    assert_eq!(WinternitzShare::from_bytes(&corrupted), Err(WinternitzError::InvalidShare));
+    assert_eq!(
+        WinternitzShare::from_bytes(&bytes[..40]),
+        Err(WinternitzError::InvalidLength)
+    );
+
+    let shares = shamir::split_privkey(&PRIVKEY, 2, 3).unwrap();
+    let privkey = shamir::combine_privkey(&shares[1..]).unwrap();
+    assert_eq!(privkey.0, PRIVKEY.0);
+    assert_eq!(shamir::combine_seed(&shares[1..]), Err(WinternitzError::InvalidLength));
+
+    assert_eq!(shamir::split_seed(&seed, 4, 3), Err(WinternitzError::InvalidShare));
+    let single = shamir::split_seed(&seed, 1, 2).unwrap();
+    assert_eq!(shamir::combine_seed(&single[1..]).unwrap(), seed);
+}
+
+#[test]
+fn test_hybrid_signature() {
+    let keypair = WinternitzHybridKeypair::<WinternitzKeccak>::new(
+        ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]),
+        WinternitzPrivkey::from(PRIVKEY.0),
+    );
+    let address = *keypair.address();
+    assert_eq!(address.winternitz, WinternitzAddress::from(ADDRESS_BYTES));
+    assert_eq!(WinternitzHybridAddress::from_bytes(&address.to_bytes()), Ok(address));
+
+    let signature = keypair.sign(MESSAGE);
+    assert_eq!(signature.verify::<WinternitzKeccak>(MESSAGE, &address), Ok(()));
+    assert!(signature.verify_winternitz::<WinternitzKeccak>(MESSAGE, &address));
+
+    let bytes = signature.to_bytes();
+    assert_eq!(bytes.len(), 1088);
+    assert_eq!(&bytes[..64], &signature.ed25519);
+    let decoded = WinternitzHybridSignature::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, signature);
+    assert_eq!(
+        WinternitzHybridSignature::from_bytes(&bytes[1..]),
+        Err(WinternitzError::InvalidLength)
+    );
+
+    // Either half failing fails the whole signature.
+    let mut tampered = WinternitzHybridSignature::from_bytes(&bytes).unwrap();
+    tampered.ed25519[0] ^= 1;
+    assert_eq!(
+        tampered.verify::<WinternitzKeccak>(MESSAGE, &address),
+        Err(WinternitzError::InvalidEd25519Signature)
+    );
+    assert!(tampered.verify_winternitz::<WinternitzKeccak>(MESSAGE, &address));
+    let mut tampered = WinternitzHybridSignature::from_bytes(&bytes).unwrap();
+    tampered.winternitz.0[3][0] ^= 1;
+    assert_eq!(
+        tampered.verify::<WinternitzKeccak>(MESSAGE, &address),
+        Err(WinternitzError::InvalidWinternitzSignature)
+    );
+    assert!(signature.verify::<WinternitzKeccak>(b"other", &address).is_err());
+
+    // Each half is bound to the other key.
+    let rebound = WinternitzHybridAddress {
+        ed25519: address.ed25519,
+        winternitz: WinternitzAddress::from([1u8; 32]),
+    };
+    assert_eq!(
+        signature.verify::<WinternitzKeccak>(MESSAGE, &rebound),
+        Err(WinternitzError::InvalidEd25519Signature)
+    );
+}
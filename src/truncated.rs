@@ -0,0 +1,160 @@
+//! Winternitz keys with chain elements truncated to `N` bytes.
+//!
+//! Every chain step, Merkle node and the address are the first `N` bytes of
+//! the full hash output, shrinking a signature from 1 KiB to `32 * N` bytes.
+//! The message digest is still the full 32-byte `hashd`, so the encoding
+//! and verification cost are unchanged.
+//!
+//! There are two ways to forge. Inverting an `N`-byte chain element is a
+//! generic preimage attack on `8N` bits. Chain steps are not tweaked by key,
+//! chain or position, so every element of every published chain is a
+//! target: one key offers `32 * 256 = 2^13` of them, and an attacker facing
+//! `2^k` keys hashed with the same `H` takes another `k` bits off. The
+//! multi-target column below is for a single key. Separately, the encoding
+//! has no checksum, so a signature for digest `v` also signs any digest `d`
+//! with every `d[i] <= v[i]`, and grinding messages finds one after about
+//! `2^45` `hashd` calls (see `WinternitzReuseAnalysis::forgeable_log2`),
+//! whatever the width:
+//!
+//! | N  | signature | preimage (classical / quantum) | multi-target, one key | encoding limit |
+//! |----|-----------|--------------------------------|-----------------------|----------------|
+//! | 16 | 512 B     | 128 / 64 bits                  | ~115 / ~57 bits       | ~45 / ~23 bits |
+//! | 20 | 640 B     | 160 / 80 bits                  | ~147 / ~73 bits       | ~45 / ~23 bits |
+//! | 24 | 768 B     | 192 / 96 bits                  | ~179 / ~89 bits       | ~45 / ~23 bits |
+//! | 32 | 1024 B    | 256 / 128 bits                 | ~243 / ~121 bits      | ~45 / ~23 bits |
+//!
+//! Security is the smaller of the multi-target bound and the encoding
+//! limit. Only a target-sum encoding (see `target_sum`) or a checksum lifts
+//! the encoding limit, and then the chain bounds are all that is left.
+//!
+//! Addresses are `N` bytes as well, so a Merkle collision between two keys
+//! costs about `2^(4N)`. Prefer 24 bytes or more unless the value at stake
+//! is small, and more still when many keys share one hash.
+
+#[cfg(not(target_os = "solana"))]
+use crate::privkey::WinternitzPrivkey;
+use crate::{hash::WinternitzHash, merkle};
+
+#[inline(always)]
+fn truncate<const N: usize>(h: [u8; 32]) -> [u8; N] {
+    const { assert!(N >= 16 && N <= 32, "chain width must be 16 to 32 bytes") };
+    let mut out = [0u8; N];
+    out.copy_from_slice(&h[..N]);
+    out
+}
+
+#[inline(always)]
+fn chain<H: WinternitzHash, const N: usize>(element: &[u8; N], steps: usize) -> [u8; N] {
+    let mut h = *element;
+    for _ in 0..steps {
+        h = truncate(H::hash(&h));
+    }
+    h
+}
+
+#[cfg(not(target_os = "solana"))]
+pub struct WinternitzTruncatedPrivkey<const N: usize>(pub [[u8; N]; 32]);
+
+#[repr(C)]
+#[derive(PartialEq)]
+pub struct WinternitzTruncatedPubkey<const N: usize>(pub [[u8; N]; 32]);
+
+#[repr(C)]
+#[derive(PartialEq)]
+pub struct WinternitzTruncatedSignature<const N: usize>(pub [[u8; N]; 32]);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WinternitzTruncatedAddress<const N: usize>(pub [u8; N]);
+
+#[cfg(not(target_os = "solana"))]
+pub type WinternitzPrivkey16 = WinternitzTruncatedPrivkey<16>;
+#[cfg(not(target_os = "solana"))]
+pub type WinternitzPrivkey20 = WinternitzTruncatedPrivkey<20>;
+#[cfg(not(target_os = "solana"))]
+pub type WinternitzPrivkey24 = WinternitzTruncatedPrivkey<24>;
+pub type WinternitzPubkey16 = WinternitzTruncatedPubkey<16>;
+pub type WinternitzPubkey20 = WinternitzTruncatedPubkey<20>;
+pub type WinternitzPubkey24 = WinternitzTruncatedPubkey<24>;
+pub type WinternitzSignature16 = WinternitzTruncatedSignature<16>;
+pub type WinternitzSignature20 = WinternitzTruncatedSignature<20>;
+pub type WinternitzSignature24 = WinternitzTruncatedSignature<24>;
+pub type WinternitzAddress16 = WinternitzTruncatedAddress<16>;
+pub type WinternitzAddress20 = WinternitzTruncatedAddress<20>;
+pub type WinternitzAddress24 = WinternitzTruncatedAddress<24>;
+
+#[cfg(not(target_os = "solana"))]
+impl<const N: usize> WinternitzTruncatedPrivkey<N> {
+    pub fn generate() -> Self {
+        Self::from(&WinternitzPrivkey::generate())
+    }
+
+    pub fn sign<H: WinternitzHash>(&self, message: &[u8]) -> WinternitzTruncatedSignature<N> {
+        let v = H::hashd(message);
+        WinternitzTruncatedSignature(core::array::from_fn(|i| {
+            chain::<H, N>(&self.0[i], 256 - v[i] as usize)
+        }))
+    }
+
+    pub fn pubkey<H: WinternitzHash>(&self) -> WinternitzTruncatedPubkey<N> {
+        WinternitzTruncatedPubkey(core::array::from_fn(|i| chain::<H, N>(&self.0[i], 256)))
+    }
+}
+
+/// Uses the first `N` bytes of each seed, e.g. of a key from `from_seed`.
+#[cfg(not(target_os = "solana"))]
+impl<const N: usize> From<&WinternitzPrivkey> for WinternitzTruncatedPrivkey<N> {
+    fn from(privkey: &WinternitzPrivkey) -> Self {
+        Self(core::array::from_fn(|i| truncate(privkey.0[i])))
+    }
+}
+
+impl<const N: usize> WinternitzTruncatedPubkey<N> {
+    pub fn merklize<H: WinternitzHash>(&self) -> [u8; N] {
+        merkle::root_by(&self.0, |left, right| truncate(H::hash_pair(left, right)))
+    }
+
+    pub fn address<H: WinternitzHash>(&self) -> WinternitzTruncatedAddress<N> {
+        WinternitzTruncatedAddress(truncate(H::hash(&self.merklize::<H>())))
+    }
+}
+
+impl<const N: usize> WinternitzTruncatedSignature<N> {
+    pub fn recover_pubkey<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+    ) -> WinternitzTruncatedPubkey<N> {
+        let v = H::hashd(message);
+        WinternitzTruncatedPubkey(core::array::from_fn(|i| {
+            chain::<H, N>(&self.0[i], v[i] as usize)
+        }))
+    }
+
+    pub fn recover_address<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+    ) -> WinternitzTruncatedAddress<N> {
+        self.recover_pubkey::<H>(message).address::<H>()
+    }
+
+    pub fn verify_address<H: WinternitzHash>(
+        &self,
+        message: &[u8],
+        address: &WinternitzTruncatedAddress<N>,
+    ) -> bool {
+        self.recover_address::<H>(message) == *address
+    }
+}
+
+impl<const N: usize> core::fmt::Display for WinternitzTruncatedAddress<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for WinternitzTruncatedAddress<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("WinternitzTruncatedAddress")
+            .field(&hex::encode(self.0))
+            .finish()
+    }
+}
@@ -0,0 +1,180 @@
+//! Keys and signatures with the hash algorithm fixed in the type.
+//!
+//! The untyped structs take `<H: WinternitzHash>` on every call, so nothing
+//! stops a pubkey derived with one hash being checked against a signature
+//! recovered with another. These wrappers carry `H` as a type parameter and
+//! only convert to other algorithms or to the untyped layer explicitly.
+
+use core::marker::PhantomData;
+
+#[cfg(not(target_os = "solana"))]
+use crate::privkey::WinternitzPrivkey;
+use crate::{
+    address::WinternitzAddress,
+    error::WinternitzError,
+    hash::WinternitzHash,
+    pubkey::WinternitzPubkey,
+    signature::{WinternitzCommitmentSignature, WinternitzExecuteSignature, WinternitzSignature},
+};
+
+macro_rules! typed_wrapper {
+    ($name:ident, $inner:ty) => {
+        pub struct $name<H: WinternitzHash> {
+            inner: $inner,
+            _hash: PhantomData<fn() -> H>,
+        }
+
+        impl<H: WinternitzHash> $name<H> {
+            /// Wraps an untyped value, asserting it was produced with `H`.
+            pub fn from_untyped(inner: $inner) -> Self {
+                Self {
+                    inner,
+                    _hash: PhantomData,
+                }
+            }
+
+            pub fn untyped(&self) -> &$inner {
+                &self.inner
+            }
+
+            pub fn into_untyped(self) -> $inner {
+                self.inner
+            }
+        }
+
+        impl<H: WinternitzHash> core::fmt::Debug for $name<H> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}<{}> ", stringify!($name), H::NAME)?;
+                core::fmt::Debug::fmt(&self.inner, f)
+            }
+        }
+    };
+}
+
+macro_rules! typed_tagged {
+    ($name:ident, $inner:ident, $len:expr) => {
+        impl<H: WinternitzHash> $name<H> {
+            pub fn to_tagged_bytes(&self) -> [u8; $len] {
+                self.inner.to_tagged_bytes::<H>()
+            }
+
+            pub fn from_tagged_bytes(bytes: &[u8]) -> Result<Self, WinternitzError> {
+                $inner::from_tagged_bytes::<H>(bytes).map(Self::from_untyped)
+            }
+        }
+    };
+}
+
+#[cfg(not(target_os = "solana"))]
+typed_wrapper!(TypedPrivkey, WinternitzPrivkey);
+typed_wrapper!(TypedPubkey, WinternitzPubkey);
+typed_wrapper!(TypedSignature, WinternitzSignature);
+typed_wrapper!(TypedCommitmentSignature, WinternitzCommitmentSignature);
+typed_wrapper!(TypedExecuteSignature, WinternitzExecuteSignature);
+typed_wrapper!(TypedAddress, WinternitzAddress);
+
+typed_tagged!(TypedPubkey, WinternitzPubkey, 1025);
+typed_tagged!(TypedSignature, WinternitzSignature, 1025);
+typed_tagged!(TypedCommitmentSignature, WinternitzCommitmentSignature, 897);
+typed_tagged!(TypedExecuteSignature, WinternitzExecuteSignature, 129);
+typed_tagged!(TypedAddress, WinternitzAddress, 33);
+
+#[cfg(not(target_os = "solana"))]
+impl<H: WinternitzHash> TypedPrivkey<H> {
+    pub fn generate() -> Self {
+        Self::from_untyped(WinternitzPrivkey::generate())
+    }
+
+    pub fn from_seed(seed: [u8; 64], path: &str) -> Result<Self, bip32::Error> {
+        WinternitzPrivkey::from_seed(seed, path).map(Self::from_untyped)
+    }
+
+    /// Reuses the same secret chains under a different hash algorithm.
+    pub fn with_hash<H2: WinternitzHash>(self) -> TypedPrivkey<H2> {
+        TypedPrivkey::from_untyped(self.inner)
+    }
+
+    pub fn sign(&self, message: &[u8]) -> TypedSignature<H> {
+        TypedSignature::from_untyped(self.inner.sign::<H>(message))
+    }
+
+    pub fn pubkey(&self) -> TypedPubkey<H> {
+        TypedPubkey::from_untyped(self.inner.pubkey::<H>())
+    }
+}
+
+impl<H: WinternitzHash> TypedPubkey<H> {
+    pub fn merklize(&self) -> [u8; 32] {
+        self.inner.merklize::<H>()
+    }
+
+    pub fn pairing_hash(&self) -> [u8; 32] {
+        self.inner.pairing_hash::<H>()
+    }
+
+    pub fn address(&self) -> TypedAddress<H> {
+        TypedAddress::from_untyped(self.inner.address::<H>())
+    }
+}
+
+impl<H: WinternitzHash> TypedSignature<H> {
+    pub fn recover_pubkey(&self, message: &[u8]) -> TypedPubkey<H> {
+        TypedPubkey::from_untyped(self.inner.recover_pubkey::<H>(message))
+    }
+
+    pub fn verify(&self, message: &[u8], pubkey: &TypedPubkey<H>) -> bool {
+        self.recover_pubkey(message) == *pubkey
+    }
+
+    pub fn split(
+        &self,
+        message: &[u8],
+    ) -> (
+        [u8; 32],
+        TypedCommitmentSignature<H>,
+        TypedExecuteSignature<H>,
+    ) {
+        let (pairing_hash, commitment, execute) = self.inner.split::<H>(message);
+        (
+            pairing_hash,
+            TypedCommitmentSignature::from_untyped(commitment),
+            TypedExecuteSignature::from_untyped(execute),
+        )
+    }
+}
+
+impl<H: WinternitzHash> TypedCommitmentSignature<H> {
+    pub fn recover_address(&self, state_hash: &[u8], pairing_hash: &[u8; 32]) -> TypedAddress<H> {
+        TypedAddress::from_untyped(self.inner.recover_address::<H>(state_hash, pairing_hash))
+    }
+}
+
+impl<H: WinternitzHash> TypedExecuteSignature<H> {
+    pub fn recover_pairing_hash(&self, message: &[u8]) -> [u8; 32] {
+        self.inner.recover_pairing_hash::<H>(message)
+    }
+}
+
+impl<H: WinternitzHash> core::fmt::Display for TypedAddress<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl<H: WinternitzHash> PartialEq for TypedPubkey<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<H: WinternitzHash> PartialEq for TypedSignature<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<H: WinternitzHash> PartialEq for TypedAddress<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
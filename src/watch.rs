@@ -0,0 +1,209 @@
+//! Watch-only tracking of exported address pools.
+//!
+//! Addresses cannot be derived without the seed, so the wallet is loaded
+//! from `WinternitzAddressPool` exports. Every observed signature is
+//! recovered to its address and the signed digest recorded; an address that
+//! has signed two different digests is flagged as reused.
+//!
+//! An execute signature only recovers the key's pairing hash. The wallet
+//! learns which address a pairing hash belongs to from a commitment or full
+//! signature of the same key, so executes seen before either are `Unknown`.
+
+use core::marker::PhantomData;
+
+use std::{collections::HashMap, string::String, vec::Vec};
+
+use crate::{
+    address::WinternitzAddress,
+    error::WinternitzError,
+    hash::WinternitzHash,
+    pool::WinternitzAddressPool,
+    signature::{WinternitzCommitmentSignature, WinternitzExecuteSignature, WinternitzSignature},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinternitzAddressStatus {
+    Unused,
+    /// A commitment has been seen but not the matching execute.
+    Used,
+    Spent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinternitzObservation {
+    /// The signature does not belong to a watched address.
+    Unknown,
+    Used(WinternitzAddress),
+    Spent(WinternitzAddress),
+    /// The address has now signed more than one digest.
+    Reused(WinternitzAddress),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzWatchEntry {
+    pub index: u32,
+    pub path: String,
+    pub status: WinternitzAddressStatus,
+    /// Distinct digests signed by this address, in the order observed.
+    pub digests: Vec<[u8; 32]>,
+}
+
+impl WinternitzWatchEntry {
+    pub fn is_reused(&self) -> bool {
+        self.digests.len() > 1
+    }
+}
+
+pub struct WinternitzWatchWallet<H: WinternitzHash> {
+    entries: HashMap<WinternitzAddress, WinternitzWatchEntry>,
+    pairing_hashes: HashMap<[u8; 32], WinternitzAddress>,
+    _hash: PhantomData<fn() -> H>,
+}
+
+impl<H: WinternitzHash> Default for WinternitzWatchWallet<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: WinternitzHash> WinternitzWatchWallet<H> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            pairing_hashes: HashMap::new(),
+            _hash: PhantomData,
+        }
+    }
+
+    /// Adds every address of `pool`, keeping the state of ones already
+    /// watched.
+    pub fn load_pool(&mut self, pool: &WinternitzAddressPool) -> Result<(), WinternitzError> {
+        if pool.hash != H::ID {
+            return Err(WinternitzError::HashMismatch {
+                expected: H::ID,
+                found: pool.hash,
+            });
+        }
+        for entry in pool.entries.iter() {
+            self.entries
+                .entry(entry.address)
+                .or_insert_with(|| WinternitzWatchEntry {
+                    index: entry.index,
+                    path: entry.path.clone(),
+                    status: WinternitzAddressStatus::Unused,
+                    digests: Vec::new(),
+                });
+        }
+        Ok(())
+    }
+
+    pub fn load_watch_only(&mut self, file: &str) -> Result<(), WinternitzError> {
+        self.load_pool(&WinternitzAddressPool::from_watch_only(file)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry(&self, address: &WinternitzAddress) -> Option<&WinternitzWatchEntry> {
+        self.entries.get(address)
+    }
+
+    pub fn status(&self, address: &WinternitzAddress) -> Option<WinternitzAddressStatus> {
+        self.entry(address).map(|entry| entry.status)
+    }
+
+    pub fn unused(&self) -> impl Iterator<Item = (&WinternitzAddress, &WinternitzWatchEntry)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.status == WinternitzAddressStatus::Unused)
+    }
+
+    pub fn reused(&self) -> impl Iterator<Item = (&WinternitzAddress, &WinternitzWatchEntry)> {
+        self.entries.iter().filter(|(_, entry)| entry.is_reused())
+    }
+
+    /// Marks a watched address as spent without observing a signature.
+    pub fn mark_spent(&mut self, address: &WinternitzAddress) -> bool {
+        match self.entries.get_mut(address) {
+            Some(entry) => {
+                entry.status = WinternitzAddressStatus::Spent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn observe_signature(
+        &mut self,
+        signature: &WinternitzSignature,
+        message: &[u8],
+    ) -> WinternitzObservation {
+        let digest = H::hashd(message);
+        let pubkey = signature.recover_pubkey_prehashed::<H>(&digest);
+        let address = pubkey.address::<H>();
+        if self.entries.contains_key(&address) {
+            self.pairing_hashes
+                .insert(pubkey.pairing_hash::<H>(), address);
+        }
+        self.record(address, digest, true)
+    }
+
+    /// `state_hash` is `H::hash(message)`, as carried by the commit
+    /// instruction.
+    pub fn observe_commitment(
+        &mut self,
+        commitment: &WinternitzCommitmentSignature,
+        state_hash: &[u8; 32],
+        pairing_hash: &[u8; 32],
+    ) -> WinternitzObservation {
+        let address = commitment.recover_address::<H>(state_hash, pairing_hash);
+        if self.entries.contains_key(&address) {
+            self.pairing_hashes.insert(*pairing_hash, address);
+        }
+        self.record(address, H::hash(state_hash), false)
+    }
+
+    pub fn observe_execute(
+        &mut self,
+        execute: &WinternitzExecuteSignature,
+        message: &[u8],
+    ) -> WinternitzObservation {
+        let pairing_hash = execute.recover_pairing_hash::<H>(message);
+        match self.pairing_hashes.get(&pairing_hash) {
+            Some(address) => self.record(*address, H::hashd(message), true),
+            None => WinternitzObservation::Unknown,
+        }
+    }
+
+    fn record(
+        &mut self,
+        address: WinternitzAddress,
+        digest: [u8; 32],
+        spent: bool,
+    ) -> WinternitzObservation {
+        let Some(entry) = self.entries.get_mut(&address) else {
+            return WinternitzObservation::Unknown;
+        };
+        if !entry.digests.contains(&digest) {
+            entry.digests.push(digest);
+        }
+        if spent {
+            entry.status = WinternitzAddressStatus::Spent;
+        } else if entry.status == WinternitzAddressStatus::Unused {
+            entry.status = WinternitzAddressStatus::Used;
+        }
+
+        if entry.is_reused() {
+            WinternitzObservation::Reused(address)
+        } else if spent {
+            WinternitzObservation::Spent(address)
+        } else {
+            WinternitzObservation::Used(address)
+        }
+    }
+}